@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use md_to_tui::{
+    options::options::{Extensions, ParseOptions},
+    parser::lexer::Lexer,
+};
+
+// lexing may fail on illegal characters but must not panic, with and without math
+fuzz_target!(|data: &[u8]| {
+    if let Ok(md) = std::str::from_utf8(data) {
+        let _ = Lexer::new().parse(&md);
+        let _ = Lexer::with_options(ParseOptions::default().extensions(Extensions::all())).parse(&md);
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use md_to_tui::{options::options::ParseOptions, style::symbols::MdSymbols, MarkdownParsable};
+
+// full pipeline with every extension and symbol set, including toc expansion
+fuzz_target!(|data: &[u8]| {
+    if let Ok(md) = std::str::from_utf8(data) {
+        let _ = md.parse_markdown(None);
+        let options = ParseOptions::gfm()
+            .extensions(md_to_tui::options::options::Extensions::all())
+            .symbols(MdSymbols::unicode())
+            .toc(true);
+        if let Ok(doc) = md.parse_with_options(None, &options) {
+            let _ = doc.toc();
+            let _ = doc.search("a");
+        }
+    }
+});
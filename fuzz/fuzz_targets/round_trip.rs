@@ -0,0 +1,104 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use md_to_tui::{
+    options::options::ParseOptions,
+    parser::lexer::{Lexer, Token},
+    MarkdownParsable,
+};
+
+fn render(md: &str) -> Option<Vec<String>> {
+    let doc = md.parse_with_options(None, &ParseOptions::commonmark()).ok()?;
+    Some(doc.text.lines.into_iter().map(String::from).collect())
+}
+
+/// byte length of the code span opened by the backtick run at the start of `rest`,
+/// the same closing run the parser looks for
+fn code_span_len(rest: &str) -> Option<usize> {
+    let mut lexer = Lexer::new();
+    let tokens = lexer.parse(&rest).ok()?;
+    let tokens = &tokens[..tokens.iter().position(Token::is_end).unwrap_or(tokens.len())];
+    let ticks = tokens.iter().take_while(|t| **t == Token::BackTick).count();
+    let mut i = ticks;
+    while i < tokens.len() {
+        let run = tokens[i..].iter().take_while(|t| **t == Token::BackTick).count();
+        if run == ticks {
+            return Some(lexer.sources[i + run - 1].end);
+        }
+        i += run.max(1);
+    }
+    None
+}
+
+/// info string of a line that looks like a code fence
+fn fence_info(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(' ');
+    let info = rest.trim_start_matches('`');
+    (rest.len() - info.len() >= 3 && !info.contains('`')).then_some(info)
+}
+
+/// escapes `\`, `&`, `<` and backticks outside code and the `[` of text read as a
+/// `[label]: url` definition, text decoded from escapes, entities and html is read back as
+/// the same text instead of being decoded again
+fn escape(lines: &[String]) -> String {
+    let mut md = String::new();
+    let mut in_code = false;
+    for (n, line) in lines.iter().enumerate() {
+        let fence = match fence_info(line) {
+            Some(_) if in_code => true,
+            // opening fences are drawn as ```` ```info ````, the block they open is closed by
+            // ```` ``` ```` or runs to the end, other fence-like lines were text
+            Some(info) => {
+                line.len() == info.len() + 3
+                    && info == info.trim()
+                    && lines[n + 1..].iter().find(|l| fence_info(l).is_some()).is_none_or(|l| l == "```")
+            }
+            None => false,
+        };
+        if fence {
+            in_code = !in_code;
+        }
+        if in_code || fence {
+            md += line;
+            md.push('\n');
+            continue;
+        }
+        let mut out = String::new();
+        let mut i = 0;
+        while let Some(c) = line[i..].chars().next() {
+            if c == '`' && !line[..i].ends_with('`') {
+                if let Some(len) = code_span_len(&line[i..]) {
+                    out += &line[i..i + len];
+                    i += len;
+                    continue;
+                }
+            }
+            if matches!(c, '\\' | '&' | '<' | '`') {
+                out.push('\\');
+            }
+            out.push(c);
+            i += c.len_utf8();
+        }
+        if render(&format!("{out}\n")).is_some_and(|l| l.is_empty()) {
+            if let Some(open) = out.find('[') {
+                out.insert(open, '\\');
+            }
+        }
+        md += &out;
+        md.push('\n');
+    }
+    md
+}
+
+// with raw symbols the output is markdown again once decoded text is escaped,
+// parsing it must give the same lines
+fuzz_target!(|data: &[u8]| {
+    let Ok(md) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Some(lines) = render(md) else {
+        return;
+    };
+    let again = escape(&lines);
+    assert_eq!(render(&again).as_ref(), Some(&lines), "input: {md:?}\nagain: {again:?}");
+});
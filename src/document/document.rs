@@ -0,0 +1,622 @@
+use std::{collections::HashMap, fmt::Display, ops::Range};
+
+use ratatui::text::{Line, Span, Text};
+use regex::Regex;
+
+use crate::style::{style::MdStyle, symbols::MdSymbols};
+
+use super::front_matter::FrontMatter;
+
+/// location in the markdown source, `start..end` is a byte range,
+/// `line` and `column` (in chars) of `start` count from 0
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceSpan {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.range().contains(&offset)
+    }
+}
+
+impl Display for SourceSpan {
+    /// `line:column` counting from 1
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
+    }
+}
+
+/// link found while parsing, `start` and `end` are columns in the rendered line,
+/// `target` is the line of the heading that `#anchor` links point to
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MdLink {
+    pub url: String,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+    pub target: Option<usize>,
+    pub source: SourceSpan,
+}
+
+/// heading found while parsing, `slug` is GitHub style anchor (`# Getting Started` -> `getting-started`)
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MdHeading {
+    pub level: usize,
+    pub text: String,
+    pub slug: String,
+    pub line: usize,
+    pub source: SourceSpan,
+}
+
+/// node of the table of contents, holds headings nested under this one
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TocEntry {
+    pub heading: MdHeading,
+    pub children: Vec<TocEntry>,
+}
+
+/// search match, `start` and `end` are columns in the rendered line
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct MdMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// what is wrong with the document, more kinds may be added
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DiagnosticKind {
+    /// `#anchor` link that does not match any heading
+    UnresolvedAnchor(String),
+    /// `[text][label]` or `[label][]` without `[label]: url` definition
+    UnresolvedReference(String),
+    /// `*`, `**`, `_` or `__` that is never closed in its paragraph
+    UnclosedEmphasis(String),
+    /// code block that runs to the end of the document
+    UnclosedFence,
+    /// heading with the same anchor as an earlier one, it gets `-1`, `-2`.. suffix
+    DuplicateAnchor(String),
+    /// table row with different number of cells than the header
+    TableCells { expected: usize, found: usize },
+    /// table header that is not followed by `|---|` row
+    MissingTableDelimiter,
+    /// url that is not written as `<url>` or `[text](url)`
+    BareUrl(String),
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnresolvedAnchor(a) => write!(f, "link to unknown anchor #{}", a),
+            Self::UnresolvedReference(r) => write!(f, "reference link to undefined label [{}]", r),
+            Self::UnclosedEmphasis(d) => write!(f, "emphasis `{}` is never closed", d),
+            Self::UnclosedFence => write!(f, "code block is never closed"),
+            Self::DuplicateAnchor(a) => write!(f, "duplicate heading anchor #{}", a),
+            Self::TableCells { expected, found } => {
+                write!(f, "table row has {} cells, header has {}", found, expected)
+            }
+            Self::MissingTableDelimiter => write!(f, "table header without delimiter row"),
+            Self::BareUrl(u) => write!(f, "bare url {}, use <{}>", u, u),
+        }
+    }
+}
+
+/// problem found in the document that didn't stop it from being parsed,
+/// `line` is the rendered line and `source` the place in the markdown
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MdDiagnostic {
+    pub kind: DiagnosticKind,
+    pub line: usize,
+    pub source: SourceSpan,
+}
+
+impl Display for MdDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.source, self.kind)
+    }
+}
+
+/// parsed markdown together with everything that was found while parsing it,
+/// `sources` holds location of every rendered line in the markdown
+#[derive(Debug, PartialEq, Clone)]
+pub struct MdDocument {
+    pub text: Text<'static>,
+    pub sources: Vec<SourceSpan>,
+    pub links: Vec<MdLink>,
+    pub headings: Vec<MdHeading>,
+    pub diagnostics: Vec<MdDiagnostic>,
+    /// lines holding only a `[TOC]` placeholder outside code blocks, set by the parser
+    pub toc_placeholders: Vec<usize>,
+    /// yaml or toml block the document starts with, when `Extensions::front_matter` is enabled
+    pub front_matter: Option<FrontMatter>,
+    pub style: MdStyle,
+    pub symbols: MdSymbols,
+}
+
+impl MdDocument {
+    pub fn new(
+        text: Text<'static>,
+        sources: Vec<SourceSpan>,
+        links: Vec<MdLink>,
+        headings: Vec<MdHeading>,
+        style: MdStyle,
+        symbols: MdSymbols,
+    ) -> MdDocument {
+        let mut doc = MdDocument {
+            text,
+            sources,
+            links,
+            headings,
+            diagnostics: vec![],
+            toc_placeholders: vec![],
+            front_matter: None,
+            style,
+            symbols,
+        };
+        doc.resolve_anchors();
+        doc
+    }
+
+    /// sets `target` of every `#anchor` link to the line of its heading,
+    /// links to missing anchors are reported in `diagnostics`
+    pub fn resolve_anchors(&mut self) {
+        self.diagnostics
+            .retain(|d| !matches!(d.kind, DiagnosticKind::UnresolvedAnchor(_)));
+        // first heading of every slug
+        let mut slugs = HashMap::new();
+        for (i, h) in self.headings.iter().enumerate() {
+            slugs.entry(h.slug.as_str()).or_insert(i);
+        }
+        for link in self.links.iter_mut() {
+            let Some(anchor) = link.url.strip_prefix('#') else {
+                continue;
+            };
+            let lower = anchor.to_lowercase();
+            link.target = [slugs.get(anchor), slugs.get(lower.as_str())]
+                .into_iter()
+                .flatten()
+                .min()
+                .map(|&i| self.headings[i].line);
+            if link.target.is_none() {
+                self.diagnostics.push(MdDiagnostic {
+                    kind: DiagnosticKind::UnresolvedAnchor(anchor.to_string()),
+                    line: link.line,
+                    source: link.source,
+                });
+            }
+        }
+        self.diagnostics.sort_by_key(|d| (d.line, d.source.start));
+    }
+
+    /// location of rendered `line` in the markdown
+    pub fn source_of(&self, line: usize) -> Option<SourceSpan> {
+        self.sources.get(line).copied()
+    }
+
+    /// first rendered line drawn from markdown at byte `offset`,
+    /// offsets between lines (line endings) belong to the line before them
+    pub fn line_at(&self, offset: usize) -> Option<usize> {
+        self.sources
+            .iter()
+            .position(|s| s.contains(offset))
+            .or_else(|| self.sources.iter().rposition(|s| s.start <= offset))
+    }
+
+    /// finds every occurrence of `pattern` in the rendered text
+    pub fn search(&self, pattern: &str) -> Vec<MdMatch> {
+        if pattern.is_empty() {
+            return vec![];
+        }
+        let regex = Regex::new(&regex::escape(pattern)).expect("escaped pattern is valid regex");
+        self.search_regex(&regex)
+    }
+
+    /// finds every match of `regex` in the rendered text, empty matches are skipped
+    pub fn search_regex(&self, regex: &Regex) -> Vec<MdMatch> {
+        let mut matches = Vec::new();
+        for (line, text) in self.text.lines.iter().enumerate() {
+            let text = String::from(text.clone());
+            for m in regex.find_iter(&text).filter(|m| !m.is_empty()) {
+                let start = Span::raw(&text[..m.start()]).width();
+                matches.push(MdMatch {
+                    line,
+                    start,
+                    end: start + Span::raw(m.as_str()).width(),
+                });
+            }
+        }
+        matches
+    }
+
+    /// headings as a tree, every heading holds following headings of deeper level
+    pub fn toc(&self) -> Vec<TocEntry> {
+        let mut entries: Vec<TocEntry> = Vec::new();
+        for heading in &self.headings {
+            insert_entry(&mut entries, heading.clone());
+        }
+        entries
+    }
+
+    /// replaces `toc_placeholders` with table of contents, every entry is a link
+    /// to its heading, placeholders stay as written when there are no headings
+    pub fn expand_toc(&mut self) {
+        if self.headings.is_empty() {
+            return;
+        }
+        let mut placeholders = std::mem::take(&mut self.toc_placeholders);
+        placeholders.retain(|&i| i < self.text.lines.len());
+        placeholders.sort_unstable();
+        placeholders.dedup();
+        // every toc holds one line per heading, so a line moves down by the
+        // placeholders above it and everything is rebuilt in a single pass
+        let added = self.headings.len();
+        let shift = |line: usize| line + placeholders.partition_point(|&p| p < line) * (added - 1);
+        let is_placeholder = |line: usize| placeholders.binary_search(&line).is_ok();
+
+        let mut links = Vec::with_capacity(self.links.len() + placeholders.len() * added);
+        let mut lines = Vec::with_capacity(self.text.lines.len() + placeholders.len() * added);
+        let mut sources = Vec::with_capacity(self.sources.len() + placeholders.len() * added);
+        for (i, line) in std::mem::take(&mut self.text.lines).into_iter().enumerate() {
+            if !is_placeholder(i) {
+                lines.push(line);
+                continue;
+            }
+            let at = shift(i);
+            for (n, (line, link)) in self.toc_lines(i).into_iter().enumerate() {
+                links.push(MdLink {
+                    line: at + n,
+                    ..link
+                });
+                lines.push(line);
+            }
+        }
+        for (i, source) in self.sources.iter().enumerate() {
+            let copies = if is_placeholder(i) { added } else { 1 };
+            sources.extend(std::iter::repeat_n(*source, copies));
+        }
+        links.extend(
+            std::mem::take(&mut self.links)
+                .into_iter()
+                .filter(|l| !is_placeholder(l.line))
+                .map(|l| MdLink {
+                    line: shift(l.line),
+                    ..l
+                }),
+        );
+        // stable, so links sharing a line keep their order
+        links.sort_by_key(|l| l.line);
+
+        self.headings
+            .iter_mut()
+            .for_each(|h| h.line = shift(h.line));
+        self.diagnostics
+            .iter_mut()
+            .for_each(|d| d.line = shift(d.line));
+        self.links = links;
+        self.text.lines = lines;
+        self.sources = sources;
+        self.resolve_anchors();
+    }
+
+    /// draws `front_matter` as a table of its keys and values above the rest of the document
+    pub fn insert_front_matter_table(&mut self) {
+        let Some(front_matter) = &self.front_matter else {
+            return;
+        };
+        let pairs = front_matter.pairs();
+        if pairs.is_empty() {
+            return;
+        }
+        let source = front_matter.source;
+        let width = |s: &str| Span::raw(s).width();
+        let keys = pairs.iter().map(|(k, _)| width(k)).max().unwrap_or(0);
+        let values = pairs.iter().map(|(_, v)| width(v)).max().unwrap_or(0);
+        let mut lines = pairs
+            .into_iter()
+            .map(|(key, value)| {
+                let key_pad = " ".repeat(keys - width(&key));
+                let value_pad = " ".repeat(values - width(&value));
+                Line::from(vec![
+                    Span::styled("| ", self.style.table),
+                    Span::styled(key, self.style.bold),
+                    Span::raw(key_pad),
+                    Span::styled(" | ", self.style.table),
+                    Span::styled(value, self.style.text),
+                    Span::raw(value_pad),
+                    Span::styled(" |", self.style.table),
+                ])
+            })
+            .collect::<Vec<_>>();
+        lines.push(Line::from(""));
+
+        let added = lines.len();
+        self.links.iter_mut().for_each(|l| l.line += added);
+        self.headings.iter_mut().for_each(|h| h.line += added);
+        self.diagnostics.iter_mut().for_each(|d| d.line += added);
+        self.toc_placeholders.iter_mut().for_each(|l| *l += added);
+        self.text.lines.splice(0..0, lines);
+        self.sources.splice(0..0, vec![source; added]);
+        self.resolve_anchors();
+    }
+
+    fn toc_lines(&self, line: usize) -> Vec<(Line<'static>, MdLink)> {
+        let min = self.headings.iter().map(|h| h.level).min().unwrap_or(1);
+        self.headings
+            .iter()
+            .map(|h| {
+                let indent = "  ".repeat(h.level - min);
+                let bullet = self.symbols.bullet(h.level - min).unwrap_or("-");
+                let start = indent.len() + Span::raw(bullet).width() + 1;
+                let link = MdLink {
+                    url: format!("#{}", h.slug),
+                    line,
+                    start,
+                    end: start + Span::raw(h.text.clone()).width(),
+                    target: None,
+                    source: self.sources.get(line).copied().unwrap_or_default(),
+                };
+                let line = Line::from(vec![
+                    Span::raw(indent),
+                    Span::styled(bullet.to_string(), self.style.list),
+                    Span::raw(" "),
+                    Span::styled(h.text.clone(), self.style.link_text),
+                ]);
+                (line, link)
+            })
+            .collect()
+    }
+}
+
+fn insert_entry(entries: &mut Vec<TocEntry>, heading: MdHeading) {
+    match entries.last_mut() {
+        Some(last) if last.heading.level < heading.level => {
+            insert_entry(&mut last.children, heading)
+        }
+        _ => entries.push(TocEntry {
+            heading,
+            children: vec![],
+        }),
+    }
+}
+
+/// GitHub style anchor, lowercase text with punctuation removed and spaces replaced by `-`
+pub fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use crate::{options::options::ParseOptions, MarkdownParsable};
+
+    use super::{slug, DiagnosticKind, MdDiagnostic, MdDocument, MdMatch, SourceSpan};
+
+    #[test]
+    fn slugs() {
+        assert_eq!(slug("Getting Started"), "getting-started");
+        assert_eq!(slug(" What's new: v1.2? "), "whats-new-v12");
+        assert_eq!(slug("snake_case and-dash"), "snake_case-and-dash");
+    }
+
+    #[test]
+    fn heading_text() -> Result<()> {
+        let md = "# [x](#y)\n# my_var\n# a &amp; \\*b\\* `c*d` *e*\n## C# ##\n# 2*3 ![logo](l.png) <b>bold</b>\n####### seven\n";
+        let doc = md.parse_document(None)?;
+        let headings = doc
+            .headings
+            .iter()
+            .map(|h| (h.text.as_str(), h.slug.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headings,
+            vec![
+                ("x", "x"),
+                ("my_var", "my_var"),
+                ("a & *b* c*d e", "a--b-cd-e"),
+                ("C#", "c"),
+                ("2*3 logo bold", "23-logo-bold"),
+            ]
+        );
+        assert_eq!(String::from(doc.text.lines[5].clone()), "####### seven");
+        Ok(())
+    }
+
+    #[test]
+    fn toc_tree_and_placeholder() -> Result<()> {
+        let mut doc = "# A
+[TOC]
+## B
+[x](y)
+### C
+## D
+"
+        .parse_document(None)?;
+
+        let toc = doc.toc();
+        assert_eq!(toc.len(), 1);
+        assert_eq!(toc[0].heading.slug, "a");
+        let children = toc[0]
+            .children
+            .iter()
+            .map(|e| (e.heading.text.as_str(), e.children.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(children, vec![("B", 1), ("D", 0)]);
+
+        doc.expand_toc();
+        let lines = doc
+            .text
+            .lines
+            .iter()
+            .map(|l| String::from(l.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(lines[1..5], ["- A", "  - B", "    - C", "  - D"]);
+        assert_eq!(doc.headings[1].line, 5);
+        assert_eq!(doc.links[1].url, "#b");
+        assert_eq!((doc.links[1].line, doc.links[1].start, doc.links[1].end), (2, 4, 5));
+        assert_eq!((doc.links[4].url.as_str(), doc.links[4].line), ("y", 6));
+        assert_eq!(doc.links[3].target, Some(8));
+        Ok(())
+    }
+
+    #[test]
+    fn toc_placeholders() -> Result<()> {
+        let text = |doc: &MdDocument| {
+            doc.text
+                .lines
+                .iter()
+                .map(|l| String::from(l.clone()))
+                .collect::<Vec<_>>()
+        };
+        let mut doc = "[TOC]\n\n```\n[TOC]\n```\n`[TOC]`\n".parse_document(None)?;
+        assert_eq!(doc.toc_placeholders, vec![0]);
+        doc.expand_toc();
+        assert_eq!(text(&doc)[0], "[TOC]");
+        assert!(doc.links.is_empty());
+
+        let mut doc = "# A\n```\n[TOC]\n```\n[TOC]\n".parse_document(None)?;
+        doc.expand_toc();
+        let lines = text(&doc);
+        assert_eq!(lines.iter().filter(|l| l.trim() == "[TOC]").count(), 1);
+        assert_eq!(lines.iter().filter(|l| l.as_str() == "- A").count(), 1);
+        assert!(doc.toc_placeholders.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn several_toc_placeholders() -> Result<()> {
+        let mut doc = "[TOC]\n# A\n[TOC]\n## B\n[b](#b)\n".parse_document(None)?;
+        doc.expand_toc();
+        let lines = doc
+            .text
+            .lines
+            .iter()
+            .map(|l| String::from(l.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(lines[..6], ["- A", "  - B", "# A", "- A", "  - B", "## B"]);
+        let links = doc
+            .links
+            .iter()
+            .map(|l| (l.url.as_str(), l.line, l.target))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                ("#a", 0, Some(2)),
+                ("#b", 1, Some(5)),
+                ("#a", 3, Some(2)),
+                ("#b", 4, Some(5)),
+                ("#b", 6, Some(5)),
+            ]
+        );
+        assert_eq!(doc.sources.len(), doc.text.lines.len());
+        Ok(())
+    }
+
+    #[test]
+    fn anchor_links() -> Result<()> {
+        let doc = "[see setup](#setup) [x](#Other) [y](#missing)
+## Setup
+# Other
+"
+        .parse_document(None)?;
+
+        let targets = doc.links.iter().map(|l| l.target).collect::<Vec<_>>();
+        assert_eq!(targets, vec![Some(1), Some(2), None]);
+        assert_eq!(
+            doc.diagnostics,
+            vec![MdDiagnostic {
+                kind: DiagnosticKind::UnresolvedAnchor("missing".into()),
+                line: 0,
+                source: SourceSpan {
+                    start: 32,
+                    end: 45,
+                    line: 0,
+                    column: 32,
+                },
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sources() -> Result<()> {
+        let md = "# Title\r\n[TOC]\n\nsee [top](#title)\n";
+        let mut doc = md.parse_document(None)?;
+
+        let heading = doc.headings[0].source;
+        assert_eq!(&md[heading.range()], "# Title");
+        let link = doc.links[0].source;
+        assert_eq!(&md[link.range()], "[top](#title)");
+        assert_eq!((link.line, link.column, link.to_string()), (3, 4, "4:5".to_string()));
+
+        assert_eq!(doc.sources.len(), doc.text.lines.len());
+        assert_eq!(doc.line_at(md.find("top").unwrap()), Some(3));
+        assert_eq!(doc.line_at(8), Some(0));
+
+        doc.expand_toc();
+        assert_eq!(doc.sources.len(), doc.text.lines.len());
+        assert_eq!(doc.links[0].source, doc.source_of(1).unwrap());
+        assert_eq!(doc.line_at(md.find("top").unwrap()), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn front_matter() -> Result<()> {
+        let md = "---\ntitle: Café {draft}\ntags: [a, b]\n---\n# Title\n[top](#title)\n";
+        let options = ParseOptions::gfm().front_matter(true);
+        let doc = md.parse_with_options(None, &options)?;
+        let lines = doc.text.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(lines[1], "[top](#title)");
+        assert_eq!(doc.front_matter.as_ref().map(|f| f.source.range()), Some(0..md.find("\n# ").unwrap()));
+        assert_eq!(&md[doc.headings[0].source.range()], "# Title");
+        assert_eq!(doc.links[0].source.to_string(), "6:1");
+        assert_eq!(doc.line_at(md.find("top").unwrap()), Some(1));
+
+        let doc = md.parse_with_options(None, &options.clone().front_matter_table(true))?;
+        let lines = doc.text.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(lines[..4], ["| title | Café {draft} |", "| tags  | [a, b]       |", "", "# Title"]);
+        assert_eq!((doc.headings[0].line, doc.links[0].line, doc.links[0].target), (3, 4, Some(3)));
+        assert_eq!(doc.source_of(0), doc.front_matter.map(|f| f.source));
+
+        let options = options.fail_on_unterminated(true);
+        let err = "---\na: b\n---\nok\n```\n".parse_with_options(None, &options).unwrap_err();
+        assert_eq!(err.span.map(|s| (s.start, s.to_string())), Some((16, "5:1".into())));
+
+        let doc = "---\na: b\n---\n".parse_document(None)?;
+        assert_eq!(doc.front_matter, None);
+        Ok(())
+    }
+
+    #[test]
+    fn search() -> Result<()> {
+        let doc = "# Setup
+run setup.sh, then SETUP again
+"
+        .parse_document(None)?;
+
+        let m = |line, start, end| MdMatch { line, start, end };
+        assert_eq!(doc.search("setup"), vec![m(1, 4, 9)]);
+        assert_eq!(doc.search(""), vec![]);
+        let regex = regex::Regex::new("(?i)setup")?;
+        assert_eq!(
+            doc.search_regex(&regex),
+            vec![m(0, 2, 7), m(1, 4, 9), m(1, 19, 24)]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use super::document::SourceSpan;
+#[cfg(feature = "front-matter")]
+use crate::error::{Error, ErrorKind, FrontMatterError};
+
+/// language of the front matter block, picked by its delimiter
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FrontMatterFormat {
+    /// between `---` lines, may be closed with `...`
+    Yaml,
+    /// between `+++` lines
+    Toml,
+}
+
+/// metadata block at the very start of the document
+///
+/// ```rust
+/// use md_to_tui::{document::front_matter::FrontMatterFormat, options::options::ParseOptions, MarkdownParsable};
+///
+/// let md = "---\ntitle: Notes\ntags: [a, b]\n---\n# Notes\n";
+/// let doc = md.parse_with_options(None, &ParseOptions::gfm().front_matter(true)).unwrap();
+/// let front_matter = doc.front_matter.unwrap();
+/// assert_eq!(front_matter.format, FrontMatterFormat::Yaml);
+/// assert_eq!(front_matter.raw, "title: Notes\ntags: [a, b]\n");
+/// assert_eq!(doc.headings[0].line, 0);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FrontMatter {
+    pub format: FrontMatterFormat,
+    /// text between the delimiter lines
+    pub raw: String,
+    /// whole block from the opening to the end of the closing delimiter
+    pub source: SourceSpan,
+}
+
+impl FrontMatter {
+    /// front matter starting on the first line of `md`, `None` if it is missing or never closed
+    pub fn find(md: &str) -> Option<FrontMatter> {
+        let mut lines = lines(md);
+        let (_, first) = lines.next()?;
+        let format = match first.trim_end() {
+            "---" => FrontMatterFormat::Yaml,
+            "+++" => FrontMatterFormat::Toml,
+            _ => return None,
+        };
+        let raw_start = lines.clone().next().map_or(md.len(), |(start, _)| start);
+        for (start, line) in lines {
+            let closes = match format {
+                FrontMatterFormat::Yaml => matches!(line.trim_end(), "---" | "..."),
+                FrontMatterFormat::Toml => line.trim_end() == "+++",
+            };
+            if closes {
+                let raw = md[raw_start..start].replace("\r\n", "\n").replace('\r', "\n");
+                return Some(FrontMatter {
+                    format,
+                    raw,
+                    source: SourceSpan {
+                        start: 0,
+                        end: start + line.len(),
+                        line: 0,
+                        column: 0,
+                    },
+                });
+            }
+        }
+        None
+    }
+
+    /// byte offset of the first line after the block
+    pub fn body_start(&self, md: &str) -> usize {
+        let rest = md.get(self.source.end..).unwrap_or_default();
+        let ending = if rest.starts_with("\r\n") {
+            2
+        } else {
+            usize::from(rest.starts_with(['\n', '\r']))
+        };
+        self.source.end + ending
+    }
+
+    /// top level `key: value` or `key = value` pairs, values of nested lines are joined with `, `
+    /// and keys of toml tables are prefixed with the table name
+    pub fn pairs(&self) -> Vec<(String, String)> {
+        let separator = match self.format {
+            FrontMatterFormat::Yaml => ':',
+            FrontMatterFormat::Toml => '=',
+        };
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        // toml `[table]` the following keys belong to
+        let mut table = String::new();
+        for line in self.raw.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if self.format == FrontMatterFormat::Toml && trimmed.starts_with('[') {
+                table = format!("{}.", trimmed.trim_matches(['[', ']']).trim());
+                continue;
+            }
+            let nested = line.starts_with([' ', '\t']) || trimmed.starts_with("- ");
+            match (nested, pairs.last_mut(), trimmed.split_once(separator)) {
+                (true, Some((_, value)), _) => {
+                    let item = trimmed.trim_start_matches("- ");
+                    if !value.is_empty() {
+                        value.push_str(", ");
+                    }
+                    value.push_str(unquote(item));
+                }
+                (false, _, Some((key, value))) => pairs.push((
+                    format!("{}{}", table, unquote(key.trim())),
+                    unquote(value.trim()).to_string(),
+                )),
+                _ => {}
+            }
+        }
+        pairs
+    }
+
+    /// block parsed with `serde_yaml` or `toml`, top level has to be a mapping
+    #[cfg(feature = "front-matter")]
+    pub fn fields(&self) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+        let value: serde_json::Value = match self.format {
+            FrontMatterFormat::Yaml if self.raw.trim().is_empty() => serde_json::Value::Null,
+            FrontMatterFormat::Yaml => serde_yaml::from_str(&self.raw).map_err(|e| self.error(e))?,
+            FrontMatterFormat::Toml => toml::from_str(&self.raw).map_err(|e| self.error(e))?,
+        };
+        match value {
+            serde_json::Value::Object(map) => Ok(map),
+            serde_json::Value::Null => Ok(serde_json::Map::new()),
+            _ => Err(Error::at(
+                ErrorKind::FrontMatter(FrontMatterError::NotMapping),
+                self.source,
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "front-matter")]
+impl FrontMatter {
+    fn error(&self, e: impl std::fmt::Display) -> Error {
+        Error::at(ErrorKind::FrontMatter(FrontMatterError::Syntax(e.to_string())), self.source)
+    }
+}
+
+/// lines with byte offset of their start, split on `\r\n`, `\n` and `\r` like the lexer
+fn lines(md: &str) -> impl Iterator<Item = (usize, &str)> + Clone {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start >= md.len() {
+            return None;
+        }
+        let rest = &md[start..];
+        let end = rest.find(['\n', '\r']).unwrap_or(rest.len());
+        let line = (start, &rest[..end]);
+        start += end + if rest[end..].starts_with("\r\n") { 2 } else { 1 };
+        Some(line)
+    })
+}
+
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .or_else(|| s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+        .unwrap_or(s)
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "front-matter")]
+    use crate::error::{ErrorKind, FrontMatterError};
+
+    use super::{FrontMatter, FrontMatterFormat};
+
+    #[test]
+    fn finding() {
+        let md = "---\r\ntitle: \"Notes\"\r\ntags:\r\n  - a\r\n  - b\r\n...\r\n# Notes";
+        let front_matter = FrontMatter::find(md).unwrap();
+        assert_eq!(front_matter.format, FrontMatterFormat::Yaml);
+        assert_eq!(front_matter.raw, "title: \"Notes\"\ntags:\n  - a\n  - b\n");
+        assert_eq!(&md[front_matter.source.range()], &md[..md.find("...").unwrap() + 3]);
+        assert_eq!(&md[front_matter.body_start(md)..], "# Notes");
+        assert_eq!(
+            front_matter.pairs(),
+            vec![("title".into(), "Notes".into()), ("tags".into(), "a, b".into())]
+        );
+
+        let md = "+++\ntitle = 'Notes'\n# comment\n[extra]\nx = 1\n+++";
+        let front_matter = FrontMatter::find(md).unwrap();
+        assert_eq!(front_matter.format, FrontMatterFormat::Toml);
+        assert_eq!(front_matter.body_start(md), md.len());
+        assert_eq!(
+            front_matter.pairs(),
+            vec![("title".into(), "Notes".into()), ("extra.x".into(), "1".into())]
+        );
+
+        assert_eq!(FrontMatter::find("---\nnot closed\n"), None);
+        assert_eq!(FrontMatter::find("text\n---\na: b\n---\n"), None);
+        assert_eq!(FrontMatter::find(""), None);
+    }
+
+    #[cfg(feature = "front-matter")]
+    #[test]
+    fn fields() -> anyhow::Result<()> {
+        let md = "---\ntitle: Notes\ntags: [a, b]\n---\n";
+        let fields = FrontMatter::find(md).unwrap().fields()?;
+        assert_eq!(fields["title"], "Notes");
+        assert_eq!(fields["tags"], serde_json::json!(["a", "b"]));
+
+        let fields = FrontMatter::find("+++\n[extra]\nx = 1\n+++\n").unwrap().fields()?;
+        assert_eq!(fields["extra"]["x"], 1);
+
+        let err = FrontMatter::find("---\n- a\n---\n").unwrap().fields().unwrap_err();
+        assert_eq!(err.kind, ErrorKind::FrontMatter(FrontMatterError::NotMapping));
+        let err = FrontMatter::find("+++\nx = \n+++\n").unwrap().fields().unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::FrontMatter(FrontMatterError::Syntax(_))));
+        Ok(())
+    }
+}
@@ -1,21 +1,253 @@
 use std::fmt::Display;
 
-#[derive(Debug, thiserror::Error, PartialEq, Clone, Default)]
-pub enum Error {
-    #[default]
-    DefErr, 
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
 
-    ParserErr(String),
-    LexerErr(String),
+use crate::document::document::SourceSpan;
+
+/// what went wrong, more kinds may be added
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    /// input is not valid UTF-8
+    InvalidUtf8,
+    /// character the lexer does not understand
+    IllegalCharacter(char),
+    /// construct that is never closed, only reported with `ParseOptions::fail_on_unterminated`
+    Unterminated(String),
+    /// quotes or lists nested deeper than `ParseOptions::max_nesting`
+    NestingLimit(usize),
+    /// reading the input failed
+    Io(std::io::ErrorKind),
+    /// theme could not be loaded or saved
+    Theme(ThemeError),
+    /// front matter could not be read with the `front-matter` feature
+    FrontMatter(FrontMatterError),
 }
-impl Display for Error {
+
+/// why a theme could not be loaded or saved, more kinds may be added
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ThemeError {
+    /// toml or json that doesn't parse, with the message of its parser
+    Syntax(String),
+    /// style name that `MdStyle` doesn't have
+    UnknownStyle(String),
+    /// color that is not a name like `light-red`, `#rrggbb` or 0-255
+    InvalidColor(String),
+    /// modifier that is not one of `bold`, `dim`, `italic`, `underlined`, `slow-blink`,
+    /// `rapid-blink`, `reversed`, `hidden` or `crossed-out`
+    InvalidModifier(String),
+    /// file extension other than `toml` or `json`
+    UnknownFormat(String),
+    /// style could not be written as toml
+    Serialize,
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(e) => write!(f, "{}", e),
+            Self::UnknownStyle(s) => write!(f, "unknown style `{}`", s),
+            Self::InvalidColor(c) => {
+                write!(f, "invalid color `{}`, expected name like `light-red`, `#rrggbb` or 0-255", c)
+            }
+            Self::InvalidModifier(m) => write!(f, "invalid modifier `{}`", m),
+            Self::UnknownFormat(e) => write!(f, "unknown theme format `{}`, expected toml or json", e),
+            Self::Serialize => write!(f, "style can't be written as toml"),
+        }
+    }
+}
+
+/// why front matter could not be read, more kinds may be added
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FrontMatterError {
+    /// yaml or toml that doesn't parse, with the message of its parser
+    Syntax(String),
+    /// top level of the block is a list or a value instead of keys
+    NotMapping,
+}
+
+impl Display for FrontMatterError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let text: String;  
         match self {
-            Self::DefErr =>  text = "Deafult error".into(),
-            Self::ParserErr(s) => text = format!("Parser Err {}", s),
-            Self::LexerErr(s) => text = format!("Parser Err {}", s),
+            Self::Syntax(e) => write!(f, "{}", e),
+            Self::NotMapping => write!(f, "top level is not a mapping"),
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Self::IllegalCharacter(c) => write!(f, "illegal character {:?}", c),
+            Self::Unterminated(what) => write!(f, "unterminated {}", what),
+            Self::NestingLimit(limit) => write!(f, "nesting deeper than {} levels", limit),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Theme(e) => write!(f, "theme error: {}", e),
+            Self::FrontMatter(e) => write!(f, "front matter error: {}", e),
+        }
+    }
+}
+
+/// error with the place in the markdown it comes from
+///
+/// ```rust
+/// use md_to_tui::{error::ErrorKind, options::options::ParseOptions, MarkdownParsable};
+///
+/// let md = "# Title\n> > > quoted too deep\n";
+/// let err = md.parse_with_options(None, &ParseOptions::default().max_nesting(2)).unwrap_err();
+/// assert_eq!(err.kind, ErrorKind::NestingLimit(2));
+/// assert_eq!(err.snippet(md), "\
+/// error: nesting deeper than 2 levels
+///  --> 2:5
+///   |
+/// 2 | > > > quoted too deep
+///   |     ^
+/// ");
+/// ```
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Option<SourceSpan>,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "md-to-tui error: {}", self.kind)?;
+        if let Some(span) = self.span {
+            write!(f, " at {}", span)?;
         }
-        write!(f, "md-to-tui error:  {}", text)
+        Ok(())
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error::new(kind)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::new(ErrorKind::Io(e.kind()))
+    }
+}
+
+const MESSAGE: Style = Style {
+    fg: Some(Color::Red),
+    bg: None,
+    add_modifier: Modifier::BOLD,
+    sub_modifier: Modifier::empty(),
+};
+const GUTTER: Style = Style {
+    fg: Some(Color::Blue),
+    bg: None,
+    add_modifier: Modifier::BOLD,
+    sub_modifier: Modifier::empty(),
+};
+const CARET: Style = Style {
+    fg: Some(Color::Red),
+    bg: None,
+    add_modifier: Modifier::BOLD,
+    sub_modifier: Modifier::empty(),
+};
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Error {
+        Error { kind, span: None }
+    }
+
+    pub fn at(kind: ErrorKind, span: SourceSpan) -> Error {
+        Error {
+            kind,
+            span: Some(span),
+        }
+    }
+
+    /// same as `snippet`, styled for drawing in the terminal
+    pub fn to_text(&self, source: &str) -> Text<'static> {
+        let mut lines = vec![Line::from(vec![
+            Span::styled("error", MESSAGE),
+            Span::styled(format!(": {}", self.kind), Style::default().add_modifier(Modifier::BOLD)),
+        ])];
+        let Some(span) = self.span else {
+            return Text::from(lines);
+        };
+        // same line endings as the lexer, `\r\n`, `\n` or `\r`
+        let normalized = source.replace("\r\n", "\n");
+        let Some(text) = normalized.lines().flat_map(|l| l.split('\r')).nth(span.line) else {
+            return Text::from(lines);
+        };
+
+        let number = (span.line + 1).to_string();
+        let pad = " ".repeat(number.len());
+        let width = source
+            .get(span.range())
+            .map(|s| s.lines().next().unwrap_or_default().chars().count())
+            .unwrap_or(0)
+            .clamp(1, (text.chars().count() + 1).saturating_sub(span.column).max(1));
+
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}--> ", pad), GUTTER),
+            Span::raw(span.to_string()),
+        ]));
+        lines.push(Line::from(Span::styled(format!("{} |", pad), GUTTER)));
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} | ", number), GUTTER),
+            Span::raw(text.to_string()),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} | ", pad), GUTTER),
+            Span::raw(" ".repeat(span.column)),
+            Span::styled("^".repeat(width), CARET),
+        ]));
+        Text::from(lines)
+    }
+
+    /// message with the line of `source` the error points to, underlined with carets
+    pub fn snippet(&self, source: &str) -> String {
+        self.to_text(source)
+            .lines
+            .into_iter()
+            .map(|l| format!("{}\n", String::from(l).trim_end()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::document::document::SourceSpan;
+
+    use super::{Error, ErrorKind};
+
+    #[test]
+    fn snippets() {
+        let source = "first\n  > > nested\n";
+        let span = SourceSpan {
+            start: 10,
+            end: 12,
+            line: 1,
+            column: 4,
+        };
+        let err = Error::at(ErrorKind::NestingLimit(1), span);
+        assert_eq!(err.to_string(), "md-to-tui error: nesting deeper than 1 levels at 2:5");
+        assert_eq!(
+            err.snippet(source),
+            "error: nesting deeper than 1 levels\n --> 2:5\n  |\n2 |   > > nested\n  |     ^^\n"
+        );
+
+        let err = Error::at(ErrorKind::NestingLimit(1), SourceSpan { line: 2, ..span });
+        assert!(err.snippet("a\r\nb\r  > > nested\r").contains("3 |   > > nested\n"));
+
+        let err = Error::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(err.kind, ErrorKind::Io(std::io::ErrorKind::NotFound));
+        assert_eq!(err.snippet(source), "error: I/O error: entity not found\n");
+        assert_eq!(err.to_text(source).lines.len(), 1);
     }
 }
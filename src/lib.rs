@@ -1,187 +1,258 @@
-//// # Usage 
-//// this library implements `MarkdownParsable` for types that implement `ToString` trait.
-//// You can use `parse_markdown` fn to parse markdown to `Text`. 
-//// `parse_markdown` takes `option` of `MdStyle` and returns `Result<Text<'static>, Error>`
-//// ```rust 
-//// example
-//// let md = "
-//// # TODO
-////
-//// - [ ] one
-//// - [ ] two 
-////
-//// [link](http://exp.com)
-////
-//// "
-//// let res = md.parse_markdown(Some(style))
-//// `
-use error::Error;
+//! # Usage
+//! this library implements `MarkdownParsable` for types that implement `ToString` trait.
+//! You can use `parse_markdown` fn to parse markdown to `Text`.
+//! `parse_markdown` takes `option` of `MdStyle` and returns `Result<Text<'static>, Error>`.
+//! It reads GitHub flavoured markdown, use `parse_with_options` with `ParseOptions::commonmark()` for plain CommonMark
+//! ```rust
+//! use md_to_tui::MarkdownParsable;
+//!
+//! let md = "
+//! # TODO
+//!
+//! - [ ] one
+//! - [ ] two
+//!
+//! [link](http://exp.com)
+//!
+//! ";
+//! let res = md.parse_markdown(None);
+//! assert!(res.is_ok());
+//! ```
+use std::io::Read;
+
+use document::{
+    document::{MdDocument, SourceSpan},
+    front_matter::FrontMatter,
+};
+use error::{Error, ErrorKind};
+use options::options::ParseOptions;
 use parser::{lexer::Lexer, parser::Parser};
 use ratatui::text::Text;
 use style::style::MdStyle;
-mod error;
+pub mod document;
+pub mod error;
+pub mod options;
+#[cfg(feature = "fuzzing")]
+pub mod parser;
+#[cfg(not(feature = "fuzzing"))]
 mod parser;
 pub mod style;
+pub mod widget;
 
 
 /// trait MarkdownParsable will take any trait that impl `ToString` and parse it into ratatui Text
 pub trait MarkdownParsable {
     /// Convert type to Text
     fn parse_markdown(&self, style: Option<MdStyle>) -> Result<Text<'static>, Error>;
+    /// Convert type to `MdDocument` which also holds links and headings found in the text
+    fn parse_document(&self, style: Option<MdStyle>) -> Result<MdDocument, Error>;
+    /// Same as `parse_document`, dialect, extensions and symbols are taken from `options`
+    fn parse_with_options(&self, style: Option<MdStyle>, options: &ParseOptions) -> Result<MdDocument, Error>;
 }
 
 impl<T> MarkdownParsable for T where T: ToString {
     fn parse_markdown(&self, style: Option<MdStyle>)  -> Result<Text<'static>, Error> {
-        let mut lexer = Lexer::new();
-        let res =  lexer.parse(self)?;
+        Ok(self.parse_document(style)?.text)
+    }
+
+    fn parse_document(&self, style: Option<MdStyle>) -> Result<MdDocument, Error> {
+        self.parse_with_options(style, &ParseOptions::default())
+    }
+
+    fn parse_with_options(&self, style: Option<MdStyle>, options: &ParseOptions) -> Result<MdDocument, Error> {
+        let md = self.to_string();
+        let front_matter = if options.extensions.front_matter { FrontMatter::find(&md) } else { None };
+        let body = front_matter.as_ref().map_or(0, |f| f.body_start(&md));
+
+        let mut lexer = Lexer::with_options(options.clone());
+        let res = lexer.parse(&&md[body..]).map_err(|e| shift_error(e, &md, body))?;
+        if body > 0 {
+            let skipped = line_count(&md[..body]);
+            lexer.sources.iter_mut().for_each(|s| shift_span(s, body, skipped));
+        }
 
         let mut parser = Parser::new(res, style);
+        parser.sources = lexer.sources;
+        parser.options = options.clone();
         let res = parser.parse()?;
 
-        Ok(Text::from(res))
+        let mut doc = MdDocument::new(
+            Text::from(res),
+            parser.line_sources,
+            parser.links,
+            parser.headings,
+            parser.style,
+            parser.options.symbols,
+        );
+        doc.diagnostics.append(&mut parser.diagnostics);
+        doc.toc_placeholders = parser.toc_placeholders;
+        doc.diagnostics.sort_by_key(|d| (d.line, d.source.start));
+        doc.front_matter = front_matter;
+        if options.front_matter_table {
+            doc.insert_front_matter_table();
+        }
+        if options.toc {
+            doc.expand_toc();
+        }
+        Ok(doc)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::{
-    error::Error,
-    io,
-    time::{Duration, Instant}, fs,
-};
-
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{
-    backend::{Backend, CrosstermBackend},
-    style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, Borders, Paragraph, Wrap},
-    Frame, Terminal,
-};
-
+/// line breaks in `text`, `\r\n`, `\n` and `\r` count as one like in the lexer
+fn line_count(text: &str) -> usize {
+    text.replace("\r\n", "\n").matches(['\n', '\r']).count()
+}
 
-struct App {
-    scroll: u16,
+/// moves span of the markdown after the front matter to its place in the whole document
+fn shift_span(span: &mut SourceSpan, offset: usize, lines: usize) {
+    span.start += offset;
+    span.end += offset;
+    span.line += lines;
 }
 
-impl App {
-    fn new() -> App {
-        App { scroll: 0 }
+fn shift_error(mut err: Error, md: &str, offset: usize) -> Error {
+    if let Some(span) = err.span.as_mut() {
+        shift_span(span, offset, line_count(&md[..offset]));
     }
+    err
+}
 
-    fn on_tick(&mut self) {
-        self.scroll += 1;
-        self.scroll %= 10;
+/// parses markdown from bytes, invalid UTF-8 is reported with its location
+pub fn parse_bytes(bytes: &[u8], style: Option<MdStyle>, options: &ParseOptions) -> Result<MdDocument, Error> {
+    match std::str::from_utf8(bytes) {
+        Ok(md) => md.parse_with_options(style, options),
+        Err(e) => {
+            let valid = &bytes[..e.valid_up_to()];
+            let line_start = valid.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+            let span = SourceSpan {
+                start: valid.len(),
+                end: valid.len() + e.error_len().unwrap_or(bytes.len() - valid.len()),
+                line: valid.iter().filter(|b| **b == b'\n').count(),
+                column: String::from_utf8_lossy(&valid[line_start..]).chars().count(),
+            };
+            Err(Error::at(ErrorKind::InvalidUtf8, span))
+        }
     }
 }
-#[test]
-#[ignore = "github action can't run tui"]
-fn ui_test() -> Result<(), Box<dyn Error>> {
-    // setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    // create app and run it
-    let tick_rate = Duration::from_millis(250);
-    let app = App::new();
-    let res = run_app(&mut terminal, app, tick_rate);
-
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
-    if let Err(err) = res {
-        println!("{err:?}");
-    }
 
-    assert_eq!(true, true);
-    Ok(())
+/// reads markdown from `reader` and parses it
+pub fn parse_reader<R: Read>(mut reader: R, style: Option<MdStyle>, options: &ParseOptions) -> Result<MdDocument, Error> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    parse_bytes(&bytes, style, options)
 }
 
-fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    mut app: App,
-    tick_rate: Duration,
-) -> io::Result<()> {
-    let mut last_tick = Instant::now();
-    loop {
-        terminal.draw(|f| ui(f, &app))?;
-
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
-        if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if let KeyCode::Char('q') = key.code {
-                    return Ok(());
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{
+        backend::TestBackend,
+        buffer::Buffer,
+        style::{Color, Style},
+        widgets::{Paragraph, Wrap},
+        Terminal,
+    };
+
+    const DOCUMENTS: [(&str, &str); 5] = [
+        ("readme", include_str!("test/readme.md")),
+        ("tasks", include_str!("test/tasks.md")),
+        ("code", include_str!("test/code.md")),
+        ("links", include_str!("test/links.md")),
+        ("test", include_str!("test/test.md")),
+    ];
+
+    const WIDTHS: [u16; 3] = [20, 40, 80];
+
+    /// `fg=Red bg=Black +BOLD`, empty for cells without colors and modifiers
+    fn style_name(style: Style) -> String {
+        let mut parts = Vec::new();
+        if let Some(fg) = style.fg.filter(|c| *c != Color::Reset) {
+            parts.push(format!("fg={fg:?}"));
+        }
+        if let Some(bg) = style.bg.filter(|c| *c != Color::Reset) {
+            parts.push(format!("bg={bg:?}"));
         }
-        if last_tick.elapsed() >= tick_rate {
-            app.on_tick();
-            last_tick = Instant::now();
+        if !style.add_modifier.is_empty() {
+            parts.push(format!("+{:?}", style.add_modifier));
         }
+        parts.join(" ")
     }
-}
 
-#[allow(dead_code)]
-fn ui<B: Backend>(f: &mut Frame<B>, _app: &App) {
-    let size = f.size();
-
-    // Words made "loooong" to demonstrate line breaking.
-    let file = fs::read("src/test/test.md").unwrap();
-    let s = "
-## TODO
-1. otehu
-
-> toehu
->> aeouth
-___
-[lol](pog.com) lool
-- long_line
-- long_line
-* toue";
-    let mut long_line = s.repeat(usize::from(size.width) / s.len() + 4);
-    long_line.push('\n');
-
-    let block = Block::default().style(Style::default().fg(Color::Black));
-    f.render_widget(block, size);
-
-    let text =  match String::from_utf8(file).unwrap().parse_markdown(None) {
-        Ok(text) => text,
-        Err(err) => Text::from(err.to_string())
-    };
-        
-    let create_block = |title| {
-        Block::default()
-            .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Gray))
-            .title(Span::styled(
-                title,
-                Style::default().add_modifier(Modifier::BOLD),
-            ))
-    };
+    /// rows of the buffer with trailing blanks trimmed, followed by runs of cells with the same
+    /// style as `row: start..end style`
+    fn buffer_snapshot(buf: &Buffer) -> String {
+        let area = buf.area;
+        let mut rows = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf.get(x, y).symbol.as_str())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        while rows.last().is_some_and(|r| r.is_empty()) {
+            rows.pop();
+        }
 
+        let mut out = rows.iter().map(|r| format!("{r}\n")).collect::<String>();
+        out.push_str("---\n");
+        for y in 0..rows.len() as u16 {
+            let mut x = 0;
+            while x < area.width {
+                let style = style_name(buf.get(x, y).style());
+                let start = x;
+                while x < area.width && style_name(buf.get(x, y).style()) == style {
+                    x += 1;
+                }
+                if !style.is_empty() {
+                    out.push_str(&format!("{y}: {start}..{x} {style}\n"));
+                }
+            }
+        }
+        out
+    }
 
+    #[test]
+    fn errors() {
+        let options = ParseOptions::default();
+        let err = parse_bytes(b"ok\nab\xffc", None, &options).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::InvalidUtf8);
+        assert_eq!(err.span.map(|s| (s.start, s.end, s.to_string())), Some((5, 6, "2:3".into())));
 
-    let paragraph = Paragraph::new(text.clone())
-        .style(Style::default().fg(Color::Gray))
-        .block(create_block("Default alignment (Left), with wrap"))
-        .wrap(Wrap { trim: true });
-    f.render_widget(paragraph, f.size());
-}
+        let err = parse_reader("> > > deep".as_bytes(), None, &options.clone().max_nesting(2)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::NestingLimit(2));
+        assert_eq!(err.span.map(|s| s.column), Some(4));
+        let md = "- a\n  - b\n    - c\n      - deep list";
+        assert!(md.parse_with_options(None, &options.clone().max_nesting(4)).is_ok());
+        assert!(md.parse_with_options(None, &options.clone().max_nesting(3)).is_err());
+        assert!("      - not nested".parse_with_options(None, &options.clone().max_nesting(1)).is_ok());
 
+        let md = "text\n```rust\ncode\n";
+        assert!(md.parse_with_options(None, &options).is_ok());
+        let err = md.parse_with_options(None, &options.clone().fail_on_unterminated(true)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Unterminated("code block".into()));
+        assert_eq!(err.snippet(md), "error: unterminated code block\n --> 2:1\n  |\n2 | ```rust\n  | ^^^^^^^\n");
+    }
+
+    #[test]
+    fn rendered_snapshots() -> anyhow::Result<()> {
+        for (name, md) in DOCUMENTS {
+            let text = md.parse_markdown(None)?;
+            for width in WIDTHS {
+                // enough rows for the narrowest width to wrap every line
+                let height = text.lines.iter().map(|l| l.width() as u16 / width + 2).sum();
+                let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+                terminal.draw(|f| {
+                    let paragraph = Paragraph::new(text.clone()).wrap(Wrap { trim: false });
+                    f.render_widget(paragraph, f.size());
+                })?;
+                insta::assert_snapshot!(
+                    format!("{name}_{width}"),
+                    buffer_snapshot(terminal.backend().buffer())
+                );
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,239 @@
+use crate::style::symbols::MdSymbols;
+
+/// markdown flavour, selects which extensions are enabled by default
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Dialect {
+    /// plain CommonMark, no extensions
+    CommonMark,
+    /// GitHub flavoured markdown: tables, task lists, strikethrough, footnotes, autolinks and admonitions
+    #[default]
+    Gfm,
+}
+
+/// syntax on top of CommonMark
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Extensions {
+    /// `| a | b |` rows
+    pub tables: bool,
+    /// `- [ ]` and `- [x]` list items
+    pub task_lists: bool,
+    /// `~~text~~`
+    pub strikethrough: bool,
+    /// `[^1]` references
+    pub footnotes: bool,
+    /// bare `https://` and `www.` urls
+    pub autolinks: bool,
+    /// `---` yaml or `+++` toml block at the start of the document
+    pub front_matter: bool,
+    /// `$inline$` and `$$display$$` TeX
+    pub math: bool,
+    /// `[[page]]` and `[[page|label]]`
+    pub wiki_links: bool,
+    /// `:rocket:` shortcodes
+    pub emoji: bool,
+    /// GitHub `> [!NOTE]` blocks
+    pub admonitions: bool,
+    /// MkDocs `!!! note` blocks with an indented body
+    pub mkdocs_admonitions: bool,
+}
+
+impl Extensions {
+    pub fn none() -> Extensions {
+        Extensions::default()
+    }
+
+    pub fn all() -> Extensions {
+        Extensions {
+            tables: true,
+            task_lists: true,
+            strikethrough: true,
+            footnotes: true,
+            autolinks: true,
+            front_matter: true,
+            math: true,
+            wiki_links: true,
+            emoji: true,
+            admonitions: true,
+            mkdocs_admonitions: true,
+        }
+    }
+
+    pub fn gfm() -> Extensions {
+        Extensions {
+            tables: true,
+            task_lists: true,
+            strikethrough: true,
+            footnotes: true,
+            autolinks: true,
+            admonitions: true,
+            ..Extensions::none()
+        }
+    }
+}
+
+impl From<Dialect> for Extensions {
+    fn from(dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::CommonMark => Extensions::none(),
+            Dialect::Gfm => Extensions::gfm(),
+        }
+    }
+}
+
+/// how raw html tags and comments are drawn
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum HtmlMode {
+    /// hide tags and comments, keep text between them
+    Strip,
+    /// draw tags and comments as written, dimmed
+    Dim,
+    /// style text of `b`, `strong`, `i`, `em`, `code`, `kbd`, `sub`, `sup` and `summary`,
+    /// break lines at `br`, hide comments and every other tag
+    #[default]
+    Interpret,
+}
+
+/// how `:rocket:` shortcodes are drawn with `Extensions::emoji`
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum EmojiMode {
+    /// emoji from the bundled table
+    #[default]
+    Unicode,
+    /// ascii like `:)` or `/!\`, shortcodes without one stay as written
+    Ascii,
+}
+
+/// options of `MarkdownParsable::parse_with_options`
+///
+/// ```rust
+/// use md_to_tui::{options::options::ParseOptions, MarkdownParsable};
+///
+/// let options = ParseOptions::commonmark().strikethrough(true).wiki_links(true);
+/// let doc = "~~old~~ [[Home]]".parse_with_options(None, &options).unwrap();
+/// assert_eq!(doc.links[0].url, "Home");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseOptions {
+    pub extensions: Extensions,
+    pub symbols: MdSymbols,
+    pub html: HtmlMode,
+    pub emoji_mode: EmojiMode,
+    /// replace `[TOC]` lines outside code blocks with table of contents, kept as written without headings
+    pub toc: bool,
+    /// draw front matter as a table of its keys and values instead of hiding it
+    pub front_matter_table: bool,
+    /// fail on code blocks that are never closed instead of closing them at the end
+    pub fail_on_unterminated: bool,
+    /// deepest quote or list nesting before parsing fails,
+    /// TeX groups nested deeper than this in math are drawn as written
+    pub max_nesting: usize,
+    /// columns between tab stops inside code blocks, tabs elsewhere stop every 4 columns
+    pub tab_width: usize,
+}
+
+/// default of `ParseOptions::max_nesting`
+pub const MAX_NESTING: usize = 32;
+
+/// default of `ParseOptions::tab_width`
+pub const TAB_WIDTH: usize = 4;
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions::new(Dialect::default())
+    }
+}
+
+macro_rules! extension_setters {
+    ($($name:ident),* $(,)?) => {
+        $(
+            pub fn $name(mut self, enabled: bool) -> ParseOptions {
+                self.extensions.$name = enabled;
+                self
+            }
+        )*
+    };
+}
+
+impl ParseOptions {
+    /// options with the default extensions of `dialect`
+    pub fn new(dialect: Dialect) -> ParseOptions {
+        ParseOptions {
+            extensions: dialect.into(),
+            symbols: MdSymbols::default(),
+            html: HtmlMode::default(),
+            emoji_mode: EmojiMode::default(),
+            toc: false,
+            front_matter_table: false,
+            fail_on_unterminated: false,
+            max_nesting: MAX_NESTING,
+            tab_width: TAB_WIDTH,
+        }
+    }
+
+    pub fn commonmark() -> ParseOptions {
+        ParseOptions::new(Dialect::CommonMark)
+    }
+
+    pub fn gfm() -> ParseOptions {
+        ParseOptions::new(Dialect::Gfm)
+    }
+
+    pub fn extensions(mut self, extensions: Extensions) -> ParseOptions {
+        self.extensions = extensions;
+        self
+    }
+
+    extension_setters!(
+        tables,
+        task_lists,
+        strikethrough,
+        footnotes,
+        autolinks,
+        front_matter,
+        math,
+        wiki_links,
+        emoji,
+        admonitions,
+        mkdocs_admonitions,
+    );
+
+    pub fn symbols(mut self, symbols: MdSymbols) -> ParseOptions {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn html(mut self, html: HtmlMode) -> ParseOptions {
+        self.html = html;
+        self
+    }
+
+    pub fn emoji_mode(mut self, emoji_mode: EmojiMode) -> ParseOptions {
+        self.emoji_mode = emoji_mode;
+        self
+    }
+
+    pub fn toc(mut self, toc: bool) -> ParseOptions {
+        self.toc = toc;
+        self
+    }
+
+    pub fn front_matter_table(mut self, front_matter_table: bool) -> ParseOptions {
+        self.front_matter_table = front_matter_table;
+        self
+    }
+
+    pub fn fail_on_unterminated(mut self, fail_on_unterminated: bool) -> ParseOptions {
+        self.fail_on_unterminated = fail_on_unterminated;
+        self
+    }
+
+    pub fn max_nesting(mut self, max_nesting: usize) -> ParseOptions {
+        self.max_nesting = max_nesting;
+        self
+    }
+
+    pub fn tab_width(mut self, tab_width: usize) -> ParseOptions {
+        self.tab_width = tab_width;
+        self
+    }
+}
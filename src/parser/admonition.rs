@@ -0,0 +1,61 @@
+/// kind of `> [!NOTE]` or `!!! note` block, picks its icon and style
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AdmonitionKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AdmonitionKind {
+    /// kind of GitHub `[!NAME]` marker, any case
+    pub fn github(name: &str) -> Option<AdmonitionKind> {
+        match name.to_lowercase().as_str() {
+            "note" => Some(AdmonitionKind::Note),
+            "tip" => Some(AdmonitionKind::Tip),
+            "important" => Some(AdmonitionKind::Important),
+            "warning" => Some(AdmonitionKind::Warning),
+            "caution" => Some(AdmonitionKind::Caution),
+            _ => None,
+        }
+    }
+
+    /// kind of MkDocs `!!! name` type, types without a kind of their own are notes
+    pub fn mkdocs(name: &str) -> AdmonitionKind {
+        match name.to_lowercase().as_str() {
+            "tip" | "hint" | "success" | "check" | "done" => AdmonitionKind::Tip,
+            "important" | "question" | "help" | "faq" | "example" => AdmonitionKind::Important,
+            "warning" | "attention" => AdmonitionKind::Warning,
+            "caution" | "danger" | "error" | "failure" | "fail" | "missing" | "bug" => {
+                AdmonitionKind::Caution
+            }
+            _ => AdmonitionKind::Note,
+        }
+    }
+
+    /// title drawn when the block doesn't set one
+    pub fn title(&self) -> &'static str {
+        match self {
+            AdmonitionKind::Note => "Note",
+            AdmonitionKind::Tip => "Tip",
+            AdmonitionKind::Important => "Important",
+            AdmonitionKind::Warning => "Warning",
+            AdmonitionKind::Caution => "Caution",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AdmonitionKind;
+
+    #[test]
+    fn kinds() {
+        assert_eq!(AdmonitionKind::github("WARNING"), Some(AdmonitionKind::Warning));
+        assert_eq!(AdmonitionKind::github("danger"), None);
+        assert_eq!(AdmonitionKind::mkdocs("danger"), AdmonitionKind::Caution);
+        assert_eq!(AdmonitionKind::mkdocs("abstract"), AdmonitionKind::Note);
+        assert_eq!(AdmonitionKind::Important.title(), "Important");
+    }
+}
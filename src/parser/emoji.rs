@@ -0,0 +1,155 @@
+/// shortcodes that are expanded, sorted by name, with their emoji and ascii form,
+/// empty ascii keeps the shortcode as written
+const SHORTCODES: &[(&str, &str, &str)] = &[
+    ("+1", "👍", "(y)"),
+    ("-1", "👎", "(n)"),
+    ("100", "💯", "100"),
+    ("airplane", "✈️", ""),
+    ("alarm_clock", "⏰", ""),
+    ("angry", "😠", ">:("),
+    ("arrow_down", "⬇️", "v"),
+    ("arrow_left", "⬅️", "<-"),
+    ("arrow_right", "➡️", "->"),
+    ("arrow_up", "⬆️", "^"),
+    ("beer", "🍺", ""),
+    ("bell", "🔔", ""),
+    ("book", "📖", ""),
+    ("books", "📚", ""),
+    ("boom", "💥", "*boom*"),
+    ("broken_heart", "💔", "</3"),
+    ("bug", "🐛", ""),
+    ("bulb", "💡", "(!)"),
+    ("calendar", "📆", ""),
+    ("cat", "🐱", ""),
+    ("chart_with_upwards_trend", "📈", ""),
+    ("check", "✔️", "[v]"),
+    ("clap", "👏", ""),
+    ("clipboard", "📋", ""),
+    ("clock", "🕐", ""),
+    ("cloud", "☁️", ""),
+    ("coffee", "☕", ""),
+    ("computer", "💻", ""),
+    ("confused", "😕", ":/"),
+    ("construction", "🚧", ""),
+    ("cool", "🆒", ""),
+    ("cry", "😢", ":'("),
+    ("dog", "🐶", ""),
+    ("email", "📧", ""),
+    ("exclamation", "❗", "!"),
+    ("eyes", "👀", ""),
+    ("fire", "🔥", ""),
+    ("flushed", "😳", ":$"),
+    ("gear", "⚙️", ""),
+    ("gift", "🎁", ""),
+    ("grin", "😁", ":D"),
+    ("grinning", "😀", ":D"),
+    ("hammer", "🔨", ""),
+    ("heart", "❤️", "<3"),
+    ("heavy_check_mark", "✔️", "[v]"),
+    ("heavy_minus_sign", "➖", "-"),
+    ("heavy_plus_sign", "➕", "+"),
+    ("hourglass", "⌛", ""),
+    ("house", "🏠", ""),
+    ("information_source", "ℹ️", "(i)"),
+    ("joy", "😂", ":'D"),
+    ("key", "🔑", ""),
+    ("kiss", "💋", ":*"),
+    ("laughing", "😆", "XD"),
+    ("link", "🔗", ""),
+    ("lock", "🔒", ""),
+    ("mag", "🔍", ""),
+    ("memo", "📝", ""),
+    ("moon", "🌙", ""),
+    ("muscle", "💪", ""),
+    ("neutral_face", "😐", ":|"),
+    ("no_entry", "⛔", ""),
+    ("no_entry_sign", "🚫", ""),
+    ("ok", "🆗", "OK"),
+    ("ok_hand", "👌", ""),
+    ("package", "📦", ""),
+    ("paperclip", "📎", ""),
+    ("pencil", "📝", ""),
+    ("pencil2", "✏️", ""),
+    ("point_down", "👇", ""),
+    ("point_left", "👈", "<="),
+    ("point_right", "👉", "=>"),
+    ("point_up", "👆", ""),
+    ("pray", "🙏", ""),
+    ("pushpin", "📌", ""),
+    ("question", "❓", "?"),
+    ("rainbow", "🌈", ""),
+    ("recycle", "♻️", ""),
+    ("robot", "🤖", ""),
+    ("rocket", "🚀", ""),
+    ("rotating_light", "🚨", "(!)"),
+    ("sad", "😞", ":("),
+    ("scream", "😱", ":O"),
+    ("see_no_evil", "🙈", ""),
+    ("shrug", "🤷", "\\_(o_o)_/"),
+    ("skull", "💀", ""),
+    ("slightly_smiling_face", "🙂", ":)"),
+    ("smile", "😄", ":)"),
+    ("smiley", "😃", ":)"),
+    ("smirk", "😏", ":>"),
+    ("sob", "😭", ":'("),
+    ("sparkles", "✨", "*"),
+    ("star", "⭐", "*"),
+    ("stuck_out_tongue", "😛", ":P"),
+    ("sunglasses", "😎", "B)"),
+    ("sunny", "☀️", ""),
+    ("tada", "🎉", ""),
+    ("thinking", "🤔", ":-?"),
+    ("thumbsdown", "👎", "(n)"),
+    ("thumbsup", "👍", "(y)"),
+    ("trophy", "🏆", ""),
+    ("truck", "🚚", ""),
+    ("umbrella", "☔", ""),
+    ("unlock", "🔓", ""),
+    ("warning", "⚠️", "/!\\"),
+    ("wave", "👋", "o/"),
+    ("white_check_mark", "✅", "[x]"),
+    ("wink", "😉", ";)"),
+    ("wrench", "🔧", ""),
+    ("x", "❌", "[X]"),
+    ("zap", "⚡", ""),
+    ("zzz", "💤", "zzz"),
+];
+
+/// `name` is a shortcode of the table
+pub fn is_shortcode(name: &str) -> bool {
+    find(name).is_some()
+}
+
+/// emoji of `:name:`, or its ascii form when `ascii` is set, `None` for unknown names
+pub fn expand(shortcode: &str, ascii: bool) -> Option<String> {
+    let name = shortcode.strip_prefix(':')?.strip_suffix(':')?;
+    let (_, emoji, fallback) = SHORTCODES[find(name)?];
+    Some(match (ascii, fallback) {
+        (false, _) => emoji.to_string(),
+        (true, "") => shortcode.to_string(),
+        (true, fallback) => fallback.to_string(),
+    })
+}
+
+fn find(name: &str) -> Option<usize> {
+    SHORTCODES.binary_search_by(|(n, _, _)| n.cmp(&name)).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{expand, is_shortcode, SHORTCODES};
+
+    #[test]
+    fn expanding() {
+        assert!(SHORTCODES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(SHORTCODES.iter().all(|(_, _, ascii)| ascii.is_ascii()));
+        assert!(is_shortcode("white_check_mark"));
+        assert!(!is_shortcode("nope"));
+        assert_eq!(expand(":rocket:", false).as_deref(), Some("🚀"));
+        assert_eq!(expand(":rocket:", true).as_deref(), Some(":rocket:"));
+        assert_eq!(expand(":warning:", true).as_deref(), Some("/!\\"));
+        assert_eq!(expand(":+1:", false).as_deref(), Some("👍"));
+        assert_eq!(expand(":nope:", false), None);
+        assert_eq!(expand("rocket", false), None);
+    }
+}
@@ -0,0 +1,55 @@
+/// html token as the parser sees it, names are lowercase
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Tag {
+    Open(String),
+    Close(String),
+    /// `<br/>`, `<img ... />`
+    SelfClosing(String),
+    /// `<!-- -->` or a line of it
+    Comment,
+}
+
+impl Tag {
+    pub fn parse(html: &str) -> Tag {
+        if !html.starts_with('<') || html.starts_with("<!--") {
+            return Tag::Comment;
+        }
+        let closing = html.starts_with("</");
+        let name = html
+            .trim_start_matches(['<', '/'])
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_lowercase();
+        if closing {
+            Tag::Close(name)
+        } else if html.ends_with("/>") {
+            Tag::SelfClosing(name)
+        } else {
+            Tag::Open(name)
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Tag::Open(name) | Tag::Close(name) | Tag::SelfClosing(name) => name,
+            Tag::Comment => "",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tag;
+
+    #[test]
+    fn tags() {
+        assert_eq!(Tag::parse("<KBD>"), Tag::Open("kbd".into()));
+        assert_eq!(Tag::parse("</b >"), Tag::Close("b".into()));
+        assert_eq!(Tag::parse("<br/>"), Tag::SelfClosing("br".into()));
+        assert_eq!(Tag::parse("<a href=\"x\">"), Tag::Open("a".into()));
+        assert_eq!(Tag::parse("<!-- x -->"), Tag::Comment);
+        assert_eq!(Tag::parse("still comment -->"), Tag::Comment);
+        assert_eq!(Tag::parse("<details open>").name(), "details");
+    }
+}
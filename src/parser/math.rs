@@ -0,0 +1,336 @@
+use super::parser_helpers::{subscript, superscript};
+
+/// commands that stand for a single symbol, sorted by name
+const SYMBOLS: &[(&str, &str)] = &[
+    ("Delta", "Δ"),
+    ("Gamma", "Γ"),
+    ("Lambda", "Λ"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("Omega", "Ω"),
+    ("Phi", "Φ"),
+    ("Pi", "Π"),
+    ("Psi", "Ψ"),
+    ("Rightarrow", "⇒"),
+    ("Sigma", "Σ"),
+    ("Theta", "Θ"),
+    ("Upsilon", "Υ"),
+    ("Xi", "Ξ"),
+    ("alpha", "α"),
+    ("approx", "≈"),
+    ("ast", "∗"),
+    ("beta", "β"),
+    ("bullet", "•"),
+    ("cap", "∩"),
+    ("cdot", "⋅"),
+    ("cdots", "⋯"),
+    ("chi", "χ"),
+    ("circ", "∘"),
+    ("cup", "∪"),
+    ("delta", "δ"),
+    ("dots", "…"),
+    ("ell", "ℓ"),
+    ("emptyset", "∅"),
+    ("epsilon", "ϵ"),
+    ("equiv", "≡"),
+    ("eta", "η"),
+    ("exists", "∃"),
+    ("forall", "∀"),
+    ("gamma", "γ"),
+    ("ge", "≥"),
+    ("geq", "≥"),
+    ("gg", "≫"),
+    ("hbar", "ℏ"),
+    ("iff", "⟺"),
+    ("iint", "∬"),
+    ("implies", "⟹"),
+    ("in", "∈"),
+    ("infty", "∞"),
+    ("int", "∫"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("land", "∧"),
+    ("langle", "⟨"),
+    ("lceil", "⌈"),
+    ("ldots", "…"),
+    ("le", "≤"),
+    ("leftarrow", "←"),
+    ("leftrightarrow", "↔"),
+    ("leq", "≤"),
+    ("lfloor", "⌊"),
+    ("ll", "≪"),
+    ("lor", "∨"),
+    ("mapsto", "↦"),
+    ("mid", "∣"),
+    ("mp", "∓"),
+    ("mu", "μ"),
+    ("nabla", "∇"),
+    ("ne", "≠"),
+    ("neg", "¬"),
+    ("neq", "≠"),
+    ("notin", "∉"),
+    ("nu", "ν"),
+    ("oint", "∮"),
+    ("omega", "ω"),
+    ("oplus", "⊕"),
+    ("otimes", "⊗"),
+    ("parallel", "∥"),
+    ("partial", "∂"),
+    ("perp", "⊥"),
+    ("phi", "ϕ"),
+    ("pi", "π"),
+    ("pm", "±"),
+    ("prime", "′"),
+    ("prod", "∏"),
+    ("propto", "∝"),
+    ("psi", "ψ"),
+    ("rangle", "⟩"),
+    ("rceil", "⌉"),
+    ("rfloor", "⌋"),
+    ("rho", "ρ"),
+    ("rightarrow", "→"),
+    ("setminus", "∖"),
+    ("sigma", "σ"),
+    ("sim", "∼"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("sum", "∑"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("tau", "τ"),
+    ("theta", "θ"),
+    ("times", "×"),
+    ("to", "→"),
+    ("upsilon", "υ"),
+    ("varepsilon", "ε"),
+    ("varphi", "φ"),
+    ("vert", "|"),
+    ("xi", "ξ"),
+    ("zeta", "ζ"),
+];
+
+/// operators written upright as their name
+const FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "cos", "cosh", "cot", "deg", "det", "dim", "exp", "gcd", "inf", "ker",
+    "lim", "ln", "log", "max", "min", "mod", "sec", "sin", "sinh", "sup", "tan", "tanh",
+];
+
+/// `tex` without its `$` delimiters drawn with unicode symbols, `None` if it uses
+/// something that can't be drawn, like environments or unknown commands, or nests
+/// groups and command arguments deeper than `max_depth`
+pub fn to_unicode(tex: &str, max_depth: usize) -> Option<String> {
+    let mut reader = Reader {
+        chars: tex.chars().collect(),
+        position: 0,
+        depth: 0,
+        max_depth,
+    };
+    let text = reader.expression(None)?;
+    Some(text.trim().to_string())
+}
+
+struct Reader {
+    chars: Vec<char>,
+    position: usize,
+    /// atoms being read inside each other
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    /// atoms up to `end` or the end of input, `end` is consumed
+    fn expression(&mut self, end: Option<char>) -> Option<String> {
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None if end.is_none() => return Some(text),
+                None => return None,
+                Some(c) if Some(c) == end => {
+                    self.position += 1;
+                    return Some(text);
+                }
+                Some('}') => return None,
+                Some(c @ ('^' | '_')) => {
+                    self.position += 1;
+                    let arg = self.argument()?;
+                    text.push_str(&script(c, &arg));
+                }
+                Some(c) if c.is_whitespace() => {
+                    self.position += 1;
+                    if !text.ends_with(' ') {
+                        text.push(' ');
+                    }
+                }
+                Some(_) => text.push_str(&self.atom()?),
+            }
+        }
+    }
+
+    /// group, command or single character
+    fn atom(&mut self) -> Option<String> {
+        if self.depth >= self.max_depth {
+            return None;
+        }
+        self.depth += 1;
+        let atom = match self.next()? {
+            '{' => self.expression(Some('}')),
+            '\\' => self.command(),
+            '&' => None,
+            c => Some(c.to_string()),
+        };
+        self.depth -= 1;
+        atom
+    }
+
+    /// atom after a command or `^`, spaces before it are skipped
+    fn argument(&mut self) -> Option<String> {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+        self.atom()
+    }
+
+    fn command(&mut self) -> Option<String> {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(c);
+            self.position += 1;
+        }
+        if name.is_empty() {
+            return match self.next()? {
+                ',' | ':' | ';' | ' ' | '\\' => Some(" ".into()),
+                '!' => Some(String::new()),
+                c @ ('{' | '}' | '%' | '$' | '#' | '&' | '_' | '|') => Some(c.to_string()),
+                _ => None,
+            };
+        }
+        if let Ok(i) = SYMBOLS.binary_search_by(|(n, _)| n.cmp(&name.as_str())) {
+            // spaces after letters only end the name, `\pi r` is `πr`
+            let symbol = SYMBOLS[i].1;
+            let spaces = self.chars[self.position..].iter().take_while(|c| c.is_whitespace()).count();
+            let letters = symbol.chars().all(char::is_alphabetic)
+                && self.chars.get(self.position + spaces).is_some_and(|c| c.is_alphanumeric());
+            if letters {
+                self.position += spaces;
+            }
+            return Some(symbol.to_string());
+        }
+        if FUNCTIONS.contains(&name.as_str()) {
+            return Some(name);
+        }
+        let text = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.argument()?;
+                let denominator = self.argument()?;
+                format!("{}/{}", parens(&numerator), parens(&denominator))
+            }
+            "sqrt" => {
+                let root = match self.peek() {
+                    Some('[') => {
+                        self.position += 1;
+                        Some(self.expression(Some(']'))?)
+                    }
+                    _ => None,
+                };
+                let sign = match root.as_deref() {
+                    None | Some("2") => "√".to_string(),
+                    Some("3") => "∛".to_string(),
+                    Some("4") => "∜".to_string(),
+                    Some(root) => format!("{}√", script('^', root)),
+                };
+                format!("{}{}", sign, parens(&self.argument()?))
+            }
+            "text" | "textrm" | "textbf" | "textit" | "mathrm" | "mathbf" | "mathit" | "mathsf"
+            | "mathtt" | "operatorname" => self.argument()?,
+            "mathbb" => self
+                .argument()?
+                .chars()
+                .map(|c| match c {
+                    'R' => Some('ℝ'),
+                    'N' => Some('ℕ'),
+                    'Z' => Some('ℤ'),
+                    'Q' => Some('ℚ'),
+                    'C' => Some('ℂ'),
+                    _ => None,
+                })
+                .collect::<Option<String>>()?,
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => match self.argument()?.as_str() {
+                "." => String::new(),
+                delimiter => delimiter.to_string(),
+            },
+            "quad" | "qquad" => "  ".into(),
+            "hat" => accent(self.argument()?, '\u{302}'),
+            "bar" | "overline" => accent(self.argument()?, '\u{305}'),
+            "tilde" => accent(self.argument()?, '\u{303}'),
+            "dot" => accent(self.argument()?, '\u{307}'),
+            "vec" => accent(self.argument()?, '\u{20d7}'),
+            _ => return None,
+        };
+        Some(text)
+    }
+}
+
+/// `text` raised or lowered if every character has a script form, else `^(text)` or `_(text)`
+fn script(mark: char, text: &str) -> String {
+    let map = if mark == '^' { superscript } else { subscript };
+    text.chars()
+        .map(map)
+        .collect::<Option<String>>()
+        .unwrap_or_else(|| format!("{}{}", mark, parens(text)))
+}
+
+/// `text` in parens if it is longer than a character
+fn parens(text: &str) -> String {
+    if text.chars().count() > 1 {
+        format!("({})", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// combining `mark` after every character of `text`
+fn accent(text: String, mark: char) -> String {
+    text.chars().flat_map(|c| [c, mark]).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::options::options::MAX_NESTING;
+
+    use super::{to_unicode, FUNCTIONS, SYMBOLS};
+
+    #[test]
+    fn converting() {
+        assert!(SYMBOLS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(FUNCTIONS.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(to_unicode("x^2 + y_{i+1}", MAX_NESTING).as_deref(), Some("x² + yᵢ₊₁"));
+        assert_eq!(to_unicode("\\alpha \\le \\beta", MAX_NESTING).as_deref(), Some("α ≤ β"));
+        assert_eq!(to_unicode("\\sum_{i=0}^{n} i", MAX_NESTING).as_deref(), Some("∑ᵢ₌₀ⁿ i"));
+        assert_eq!(to_unicode("\\int_0^\\infty f", MAX_NESTING).as_deref(), Some("∫₀^∞ f"));
+        assert_eq!(to_unicode("\\frac{a+b}{2}", MAX_NESTING).as_deref(), Some("(a+b)/2"));
+        assert_eq!(to_unicode("\\sqrt[3]{x} \\in \\mathbb{R}", MAX_NESTING).as_deref(), Some("∛x ∈ ℝ"));
+        assert_eq!(to_unicode("\\sin\\left(\\theta\\right)", MAX_NESTING).as_deref(), Some("sin(θ)"));
+        assert_eq!(to_unicode("x_{\\alpha}", MAX_NESTING).as_deref(), Some("x_α"));
+        assert_eq!(to_unicode("\\text{if } x\\,y", MAX_NESTING).as_deref(), Some("if x y"));
+        assert_eq!(to_unicode("\\begin{matrix} a & b \\end{matrix}", MAX_NESTING), None);
+        assert_eq!(to_unicode("\\frac{a}", MAX_NESTING), None);
+        assert_eq!(to_unicode("{x", MAX_NESTING), None);
+        assert_eq!(to_unicode("x}", MAX_NESTING), None);
+        assert_eq!(to_unicode("{{x}}", 3).as_deref(), Some("x"));
+        assert_eq!(to_unicode("{{x}}", 2), None);
+        assert_eq!(to_unicode(&"\\frac".repeat(200_000), MAX_NESTING), None);
+        let deep = format!("{}x{}", "{".repeat(200_000), "}".repeat(200_000));
+        assert_eq!(to_unicode(&deep, MAX_NESTING), None);
+    }
+}
@@ -1,3 +1,9 @@
+#[allow(clippy::module_inception)]
 pub mod parser;
 pub mod parser_helpers;
+pub mod admonition;
+pub mod emoji;
+pub mod entities;
+pub mod html;
+pub mod math;
 pub mod lexer;
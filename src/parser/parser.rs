@@ -1,13 +1,26 @@
-use std::usize;
+use std::collections::HashMap;
 
-use ratatui::text::{Line, Span};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
 
-use crate::{error::Error, style::style::MdStyle};
+use crate::{
+    document::document::{slug, DiagnosticKind, MdDiagnostic, MdHeading, MdLink, SourceSpan},
+    error::{Error, ErrorKind},
+    options::options::{EmojiMode, HtmlMode, ParseOptions},
+    style::style::MdStyle,
+};
 
 use super::{
+    admonition::AdmonitionKind,
+    emoji, entities,
+    html::Tag,
     lexer::Token,
+    math,
     parser_helpers::{
         genarate_list_start, generate_heading, generate_horizontal_rule, generate_indent,
+        subscript, superscript,
     },
 };
 
@@ -28,23 +41,54 @@ use super::{
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct Parser {
     pub input: Vec<Token>,
+    /// location of every token of `input`, from `Lexer::sources`
+    pub sources: Vec<SourceSpan>,
     pub style: MdStyle,
+    pub options: ParseOptions,
 
     pub position: usize,
     pub read_position: usize,
     pub token: Token,
 
     pub lines: Vec<Line<'static>>,
+    /// location of every line in `lines`
+    pub line_sources: Vec<SourceSpan>,
+    pub links: Vec<MdLink>,
+    pub headings: Vec<MdHeading>,
+    /// slugs taken by `headings`, with the last suffix tried when it was repeated
+    pub slugs: HashMap<String, usize>,
+    pub diagnostics: Vec<MdDiagnostic>,
+    /// lines holding only a `[TOC]` placeholder outside code blocks
+    pub toc_placeholders: Vec<usize>,
+    /// urls of `[label]: url` definitions by normalized label
+    pub definitions: HashMap<String, String>,
+    /// token positions of the `[label]: url` lines, read up front and not drawn
+    pub definition_lines: Vec<usize>,
+    pub in_code: bool,
+    /// token position and line of the fence that opened the code block
+    pub code_start: usize,
+    pub code_line: usize,
+    /// code block is a ```` ```math ```` fence
+    pub code_math: bool,
+    pub table_row: bool,
+    /// cell count of the table header, whether its delimiter row was seen, its token position and line,
+    /// no cells once the header turned out to be missing its delimiter row
+    pub table: Option<(usize, bool, usize, usize)>,
+    /// emphasis delimiters open in the current paragraph, their token, position, length and line
+    pub emphasis: Vec<(Token, usize, usize, usize)>,
+    /// spans of the line being read that start a new line, drawn for `<br>`
+    pub breaks: Vec<usize>,
+    /// kind of the admonition being read and whether it is an indented MkDocs block
+    pub admonition: Option<(AdmonitionKind, bool)>,
+    /// content columns of the list items the line may be nested in, outermost first
+    pub list_items: Vec<usize>,
 }
 
 impl Parser {
     pub fn new(input: Vec<Token>, style: Option<MdStyle>) -> Parser {
         // info!("created new Parser");
 
-        let style = match style {
-            Some(style) => style,
-            None => MdStyle::default(),
-        };
+        let style = style.unwrap_or_default();
 
         let parser = Parser {
             input,
@@ -54,36 +98,145 @@ impl Parser {
 
         // info!("Parser {:?}", parser);
 
-        return parser;
+        parser
     }
 
     pub fn parse(&mut self) -> Result<Vec<Line<'static>>, Error> {
+        self.expand_tabs();
+        self.position = 0;
+        self.read_position = 0;
         self.read_token();
 
-        let mut lines: Vec<Line> = Vec::new();
-        self.next_line()?;
-        let mut end = !(self.position >= self.input.len());
-        while end {
-            // info!("IS_END {}", end);
+        self.lines.clear();
+        self.line_sources.clear();
+        self.links.clear();
+        self.headings.clear();
+        self.slugs.clear();
+        self.diagnostics.clear();
+        self.toc_placeholders.clear();
+        self.emphasis.clear();
+        self.table = None;
+        self.in_code = false;
+        self.code_math = false;
+        self.admonition = None;
+        self.list_items.clear();
+        self.read_definitions();
+        while self.position < self.input.len() {
             // info!("Positon/Len/Tk {}/{}/{}", self.position, self.input.len(), self.token);
+            if self.definition_lines.contains(&self.position) {
+                self.skip(self.rest_of_line().len() + 1);
+                continue;
+            }
+            let line_start = self.position;
+            let in_code = self.in_code;
             let next = self.next_line()?;
             // info!("Next Line {:#?}", next);
-            end = !(self.position >= self.input.len());
-            lines.push(next);
+            // `next_line` stops after the end of line token
+            let line_end = self.position.saturating_sub(1).max(line_start);
+            let source = self.source(line_start, line_end);
+            if !in_code && !self.in_code && self.toc_placeholder(line_start, &next) {
+                self.toc_placeholders.push(self.lines.len());
+            }
+            for line in self.split_at_breaks(next) {
+                self.lines.push(line);
+                self.line_sources.push(source);
+            }
         }
+        self.close_paragraph();
+        if self.in_code {
+            let fence = self.code_start + self.rest_of_line_at(self.code_start).len();
+            if self.options.fail_on_unterminated {
+                return Err(Error::at(
+                    ErrorKind::Unterminated("code block".into()),
+                    self.source(self.code_start, fence),
+                ));
+            }
+            self.diagnose(
+                DiagnosticKind::UnclosedFence,
+                self.code_line,
+                self.code_start,
+                fence,
+            );
+        }
+        self.diagnostics.sort_by_key(|d| (d.line, d.source.start));
 
         // info!("Lines {:#?}", lines);
 
-        self.lines = lines.clone();
-        Ok(lines)
+        Ok(self.lines.clone())
     }
 
     fn next_line<'a>(&mut self) -> Result<Line<'a>, Error> {
         let mut spans: Vec<Span> = Vec::new();
+        // token position of the closing paren, starting column, url and first token of the link being read
+        let mut link: Option<(usize, usize, String, usize)> = None;
+        // token position where the style ends, first span and style to patch spans with
+        let mut ranges: Vec<(usize, usize, Style)> = Vec::new();
+        // style of the span before any range and styles of the ranges around it, outermost first
+        let mut nested: Vec<Option<(Style, Vec<Style>)>> = Vec::new();
+        // token position of the backtick closing the code span being read
+        let mut code_span: Option<usize> = None;
+        // token position of the closing `</sub>` or `</sup>`, first span and character mapping
+        let mut scripts: Vec<(usize, usize, Script)> = Vec::new();
+        let mut breaks: Vec<usize> = Vec::new();
+        let line_start = self.position;
+        self.check_table();
+        self.line_prefix(&mut spans)?;
+        self.check_emphasis();
         while !self.token.is_end() {
             // info!("Is end {}", self.token.is_end());
 
+            let mut heading_start = false;
+            if let Token::Heading(level) = self.token {
+                let at_start = self.input[line_start..self.position]
+                    .iter()
+                    .all(|t| *t == Token::WhiteSpace || *t == Token::RightAngle);
+                // `#######` and longer runs are text
+                if at_start
+                    && level <= 6
+                    && !self.in_code
+                    && (self.peek() == Token::WhiteSpace || self.peek().is_end())
+                {
+                    heading_start = true;
+                    self.read_heading(level);
+                }
+            }
+
+            let run_start = self.position == 0 || self.input[self.position - 1] != Token::BackTick;
+            if code_span.is_none() && self.token == Token::BackTick && run_start {
+                code_span = self.code_span_end();
+            }
+            let literal = self.in_code || code_span.is_some();
+
+            if link.is_none() && !literal {
+                self.check_references();
+            }
+
+            // links in code are drawn as written
+            if link.is_none() && !literal {
+                if let Some((end, url, style)) = self.link_target() {
+                    link = Some((end, line_width(&spans), url, self.position));
+                    if !self.options.symbols.link_marker.is_empty() {
+                        spans.push(Span::styled(self.options.symbols.link_marker.clone(), self.style.link));
+                    }
+                    if let Some(style) = style {
+                        ranges.push((end, spans.len(), style));
+                    }
+                }
+            }
+
+            if let Some(end) = self.strikethrough_end(&ranges) {
+                let crossed = Style::default().add_modifier(Modifier::CROSSED_OUT);
+                ranges.push((end, spans.len(), crossed));
+            }
+
+            if let Some(end) = self.footnote_end() {
+                ranges.push((end, spans.len(), self.style.link_text));
+            }
+
             spans.push(match &self.token {
+                Token::Heading(heading) if heading_start => {
+                    Span::styled(self.options.symbols.heading(*heading).to_string(), self.style.heading)
+                }
                 Token::Heading(heading) => generate_heading(heading, &self.style),
                 Token::Asterisk | Token::Dash | Token::Plus => {
                     genarate_list_start(self.token.clone(), &self.style)
@@ -119,20 +272,1089 @@ impl Parser {
                 Token::Colon => Span::styled(":", self.style.text),
                 Token::SemiColon => Span::styled(";", self.style.text),
                 Token::Slash => Span::styled("/", self.style.text),
+                Token::Pipe if self.table_row => Span::styled("|", self.style.table),
+                Token::Tilde
+                | Token::Pipe
+                | Token::Caret
+                | Token::BackSlash
+                | Token::Ampersand
+                | Token::Dollar
+                | Token::Bang => Span::styled(self.token.literal(), self.style.text),
+                Token::Escaped(c) if !literal => Span::styled(c.to_string(), self.style.text),
+                Token::Entity(e) if !literal => {
+                    Span::styled(entities::decode(e).unwrap_or(e.clone()), self.style.text)
+                }
+                Token::Html(html) if !literal => {
+                    self.html_span(html.clone(), spans.len(), &mut ranges, &mut scripts, &mut breaks)
+                }
+                Token::Emoji(code) if self.options.extensions.emoji && !literal => {
+                    let ascii = self.options.emoji_mode == EmojiMode::Ascii;
+                    Span::styled(emoji::expand(code, ascii).unwrap_or(code.clone()), self.style.text)
+                }
+                Token::Math(tex) if self.options.extensions.math && (!literal || self.code_math && code_span.is_none()) => {
+                    math_span(tex, &self.style, self.options.max_nesting)
+                }
+                Token::Escaped(_) | Token::Entity(_) | Token::Html(_) | Token::Emoji(_) | Token::Math(_) => {
+                    Span::styled(self.token.literal(), self.style.text)
+                }
 
-                _ => Span::from(format!("TODO: {}", self.token.to_string())),
+                _ => Span::from(format!("TODO: {}", self.token)),
             });
 
+            let position = self.position;
+            if code_span == Some(position) {
+                code_span = None;
+            }
+            // ranges closing later are outer ones, inner range styles win over them
+            for (_, start, style) in ranges.iter().rev().filter(|r| r.0 == position) {
+                nested.resize_with(spans.len(), || None);
+                for (span, nested) in spans[*start..].iter_mut().zip(&mut nested[*start..]) {
+                    let (base, styles) = nested.get_or_insert_with(|| (span.style, Vec::new()));
+                    styles.insert(0, *style);
+                    span.style = base.patch(self.style.nested(styles));
+                }
+            }
+            ranges.retain(|r| r.0 != position);
+            for (_, start, script) in scripts.iter().filter(|r| r.0 == position) {
+                for span in spans[*start..].iter_mut() {
+                    let text = span.content.chars().map(|c| script(c).unwrap_or(c)).collect::<String>();
+                    span.content = text.into();
+                }
+            }
+            scripts.retain(|r| r.0 != position);
+
             // info!("Spans {:#?}", spans);
 
+            if let Some((end, start, url, first)) = link.take() {
+                if end == self.position {
+                    self.links.push(MdLink {
+                        url,
+                        line: self.lines.len(),
+                        start,
+                        end: line_width(&spans),
+                        target: None,
+                        source: self.source(first, end + 1),
+                    });
+                } else {
+                    link = Some((end, start, url, first));
+                }
+            }
+
             self.read_token();
         }
         self.read_token();
+        self.breaks = breaks;
         let line = Line::from(spans);
         // info!("Line {:#?}", line);
         Ok(line)
     }
 
+    /// span drawn for html token at the current position, styles of tags closed on the same line
+    /// are added to `ranges` and `scripts`
+    fn html_span(
+        &self,
+        html: String,
+        first: usize,
+        ranges: &mut Vec<(usize, usize, Style)>,
+        scripts: &mut Vec<(usize, usize, Script)>,
+        breaks: &mut Vec<usize>,
+    ) -> Span<'static> {
+        match self.options.html {
+            HtmlMode::Strip => return Span::raw(""),
+            HtmlMode::Dim => return Span::styled(html, self.style.text.add_modifier(Modifier::DIM)),
+            HtmlMode::Interpret => {}
+        }
+        let tag = Tag::parse(&html);
+        if matches!(tag, Tag::Open(_) | Tag::SelfClosing(_)) && tag.name() == "br" {
+            breaks.push(first);
+            return Span::raw("");
+        }
+        let Tag::Open(name) = tag else {
+            return Span::raw("");
+        };
+        let Some(end) = self.closing_tag(&name) else {
+            return Span::raw("");
+        };
+        match name.as_str() {
+            "b" | "strong" => ranges.push((end, first, self.style.bold)),
+            "i" | "em" => ranges.push((end, first, self.style.italic)),
+            "code" => ranges.push((end, first, self.style.backtick)),
+            "kbd" => ranges.push((end, first, self.style.tag)),
+            "sub" => scripts.push((end, first, subscript)),
+            "sup" => scripts.push((end, first, superscript)),
+            "summary" => {
+                ranges.push((end, first, self.style.bold));
+                return Span::styled(self.options.symbols.summary.clone(), self.style.list);
+            }
+            _ => {}
+        }
+        Span::raw("")
+    }
+
+    /// position of the tag closing `name` opened at the current token, on the same line
+    fn closing_tag(&self, name: &str) -> Option<usize> {
+        let mut depth = 0;
+        for (i, token) in self.rest_of_line().iter().enumerate().skip(1) {
+            let Token::Html(html) = token else {
+                continue;
+            };
+            match Tag::parse(html) {
+                Tag::Open(n) if n == name => depth += 1,
+                Tag::Close(n) if n == name && depth == 0 => return Some(self.position + i),
+                Tag::Close(n) if n == name => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// splits line at spans of `<br>` tags, links of the line move to the parts they cover,
+    /// a link spanning a break gets one range on each of its lines
+    fn split_at_breaks(&mut self, line: Line<'static>) -> Vec<Line<'static>> {
+        let breaks = std::mem::take(&mut self.breaks);
+        if breaks.is_empty() {
+            return vec![line];
+        }
+        let mut parts: Vec<Vec<Span<'static>>> = vec![vec![]];
+        for (i, span) in line.spans.into_iter().enumerate() {
+            if breaks.contains(&i) {
+                parts.push(vec![]);
+            }
+            parts.last_mut().expect("parts are never empty").push(span);
+        }
+        // `<br>` at the end of the line doesn't add an empty line
+        while parts.len() > 1 && line_width(&parts[parts.len() - 1]) == 0 {
+            let last = parts.pop().unwrap_or_default();
+            parts.last_mut().expect("parts are never empty").extend(last);
+        }
+
+        let line = self.lines.len();
+        let offsets = parts
+            .iter()
+            .scan(0, |width, part| {
+                let start = *width;
+                *width += line_width(part);
+                Some((start, *width))
+            })
+            .collect::<Vec<_>>();
+        for link in std::mem::take(&mut self.links) {
+            if link.line != line {
+                self.links.push(link);
+                continue;
+            }
+            let first = offsets.iter().rposition(|o| o.0 <= link.start).unwrap_or(0);
+            for (part, (start, end)) in offsets.iter().enumerate().skip(first) {
+                if part > first && *start >= link.end {
+                    break;
+                }
+                self.links.push(MdLink {
+                    line: line + part,
+                    start: link.start.max(*start) - start,
+                    end: link.end.min(*end).max(link.start) - start,
+                    ..link.clone()
+                });
+            }
+        }
+        parts.into_iter().map(Line::from).collect()
+    }
+
+    /// tokens from the current one to the end of the line
+    fn rest_of_line(&self) -> &[Token] {
+        self.rest_of_line_at(self.position)
+    }
+
+    /// line starting at token `position` is written and drawn as only `[TOC]`
+    fn toc_placeholder(&self, position: usize, line: &Line) -> bool {
+        let written = self.rest_of_line_at(position).iter().map(Token::literal).collect::<String>();
+        let drawn = line.spans.iter().map(|s| s.content.as_ref()).collect::<String>();
+        written.trim() == "[TOC]" && drawn.trim() == "[TOC]"
+    }
+
+    fn rest_of_line_at(&self, position: usize) -> &[Token] {
+        let rest = self.input.get(position..).unwrap_or(&[]);
+        let end = rest.iter().position(|t| t.is_end()).unwrap_or(rest.len());
+        &rest[..end]
+    }
+
+    fn skip(&mut self, n: usize) {
+        for _ in 0..n {
+            self.read_token();
+        }
+    }
+
+    /// draws code fences, rules, quotes and list bullets at the start of the line with `MdSymbols`
+    fn line_prefix(&mut self, spans: &mut Vec<Span<'static>>) -> Result<(), Error> {
+        self.table_row = false;
+        self.admonition_prefix(spans);
+        let rest = self.rest_of_line();
+        let lead = rest.iter().take_while(|t| **t == Token::WhiteSpace).count();
+        let ticks = rest[lead..].iter().take_while(|t| **t == Token::BackTick).count();
+        let len = rest.len();
+
+        // info string of a backtick fence can't contain backticks
+        let info = &rest[lead + ticks..];
+        if ticks >= 3 && !info.iter().any(|t| t.literal().contains('`')) {
+            let lang = info.iter().map(|t| t.literal()).collect::<String>();
+            let fence = if self.in_code {
+                self.options.symbols.code_bottom.clone()
+            } else {
+                format!("{}{}", self.options.symbols.code_top, lang.trim())
+            };
+            spans.push(Span::styled(fence, self.style.backtick));
+            if !self.in_code {
+                self.code_start = self.position + lead;
+                self.code_line = self.lines.len();
+                self.code_math = lang.trim() == "math";
+            }
+            self.in_code = !self.in_code;
+            self.skip(len);
+            return Ok(());
+        }
+
+        if self.in_code {
+            if !self.options.symbols.code_side.is_empty() {
+                spans.push(Span::styled(self.options.symbols.code_side.clone(), self.style.backtick));
+            }
+            return Ok(());
+        }
+
+        let marks = rest.iter().filter(|t| **t != Token::WhiteSpace).collect::<Vec<_>>();
+        let is_rule = marks.len() >= 3
+            && matches!(marks[0], Token::Dash | Token::Asterisk | Token::Undersocre)
+            && marks.iter().all(|t| *t == marks[0]);
+        if is_rule {
+            let rule = if self.options.symbols.rule.is_empty() {
+                rest.iter().map(|t| t.literal()).collect::<String>()
+            } else {
+                self.options.symbols.rule.repeat(self.options.symbols.rule_width)
+            };
+            spans.push(Span::styled(rule, self.style.horizontal_rule));
+            self.skip(len);
+            return Ok(());
+        }
+
+        if self.options.extensions.tables && rest.get(lead) == Some(&Token::Pipe) {
+            let delimiter = rest.iter().all(|t| {
+                matches!(t, Token::Pipe | Token::Dash | Token::Colon | Token::WhiteSpace)
+            });
+            if delimiter {
+                let row = rest.iter().map(|t| t.literal()).collect::<String>();
+                spans.push(Span::styled(row, self.style.table));
+                self.skip(len);
+                return Ok(());
+            }
+            self.table_row = true;
+        }
+
+        if self.options.extensions.mkdocs_admonitions {
+            if let Some((kind, title)) = self.mkdocs_admonition() {
+                self.admonition = Some((kind, true));
+                spans.push(Span::styled(self.options.symbols.quote.clone(), self.style.admonition(kind)));
+                spans.push(Span::from(" "));
+                spans.push(self.admonition_title(kind, &title));
+                self.skip(len);
+                return Ok(());
+            }
+        }
+
+        for _ in 0..lead {
+            spans.push(Span::from(" "));
+        }
+        self.skip(lead);
+
+        let quote_start = spans.len();
+        let mut depth = 0;
+        while self.token == Token::RightAngle {
+            depth += 1;
+            if depth > self.options.max_nesting {
+                return Err(self.nesting_error());
+            }
+            spans.push(Span::styled(self.options.symbols.quote.clone(), self.style.blocqoutes));
+            self.read_token();
+            while self.token == Token::WhiteSpace {
+                spans.push(Span::from(" "));
+                self.read_token();
+            }
+        }
+
+        if depth > 0 && self.options.extensions.admonitions {
+            let marker = self.github_admonition();
+            if let Some(kind) = marker {
+                self.admonition = Some((kind, false));
+            }
+            if let Some((kind, false)) = self.admonition {
+                spans[quote_start].style = self.style.admonition(kind);
+            }
+            if let Some(kind) = marker {
+                spans.push(self.admonition_title(kind, kind.title()));
+                self.skip(self.rest_of_line().len());
+                return Ok(());
+            }
+        }
+
+        let list_depth = self.list_item(lead);
+        let is_bullet = matches!(self.token, Token::Dash | Token::Asterisk | Token::Plus)
+            && self.peek() == Token::WhiteSpace;
+        if !is_bullet {
+            return Ok(());
+        }
+        let list_depth = list_depth.unwrap_or_default();
+        if list_depth >= self.options.max_nesting {
+            return Err(self.nesting_error());
+        }
+        let bullet = match self.options.symbols.bullet(list_depth) {
+            Some(b) => b.to_string(),
+            None => self.token.literal(),
+        };
+        spans.push(Span::styled(bullet, self.style.list));
+        spans.push(Span::from(" "));
+        self.skip(2);
+
+        if !self.options.extensions.task_lists {
+            return Ok(());
+        }
+        let checkbox = match self.rest_of_line() {
+            [Token::LeftSquare, Token::WhiteSpace, Token::RightSquare, ..] => {
+                Some(self.options.symbols.unchecked.clone())
+            }
+            [Token::LeftSquare, Token::Indent(x), Token::RightSquare, ..]
+                if x == "x" || x == "X" =>
+            {
+                Some(self.options.symbols.checked.clone())
+            }
+            _ => None,
+        };
+        if let Some(checkbox) = checkbox {
+            spans.push(Span::styled(checkbox, self.style.list));
+            self.skip(3);
+        }
+        Ok(())
+    }
+
+    /// nesting of the list item starting at the current token, indented by `lead` columns,
+    /// `None` if the line is not a list item. Items are nested in the items whose content
+    /// column they are indented to, the rest of them are closed
+    fn list_item(&mut self, lead: usize) -> Option<usize> {
+        let marker = match self.rest_of_line() {
+            [] => return None,
+            [Token::Dash | Token::Asterisk | Token::Plus, Token::WhiteSpace, ..] => Some(1),
+            [Token::Indent(n), Token::Dot | Token::LeftParen, Token::WhiteSpace, ..]
+                if n.bytes().all(|c| c.is_ascii_digit()) =>
+            {
+                Some(n.len() + 1)
+            }
+            _ => None,
+        };
+        // text right after an item may continue it lazily, after a blank line it has to be indented
+        if marker.is_some() || self.previous_line_blank() {
+            while self.list_items.last().is_some_and(|column| lead < *column) {
+                self.list_items.pop();
+            }
+        }
+        let depth = self.list_items.len();
+        self.list_items.push(lead + marker? + 1);
+        Some(depth)
+    }
+
+    /// line before the current one has no text other than quote markers, or there is none
+    fn previous_line_blank(&self) -> bool {
+        let before = &self.input[..self.position];
+        let Some(end) = before.iter().rposition(|t| t.is_end()) else {
+            return true;
+        };
+        before[..end]
+            .iter()
+            .rev()
+            .take_while(|t| !t.is_end())
+            .all(|t| matches!(t, Token::WhiteSpace | Token::Tab | Token::RightAngle))
+    }
+
+    /// border of the admonition the line continues, ends the admonition when the line doesn't
+    fn admonition_prefix(&mut self, spans: &mut Vec<Span<'static>>) {
+        let Some((kind, mkdocs)) = self.admonition else {
+            return;
+        };
+        let rest = self.rest_of_line();
+        let lead = rest.iter().take_while(|t| **t == Token::WhiteSpace).count();
+        if !mkdocs {
+            if rest.get(lead) != Some(&Token::RightAngle) {
+                self.admonition = None;
+            }
+            return;
+        }
+        let blank = lead == rest.len();
+        if (blank && !self.indented_line_follows()) || (!blank && lead < 4) {
+            self.admonition = None;
+            return;
+        }
+        spans.push(Span::styled(self.options.symbols.quote.clone(), self.style.admonition(kind)));
+        spans.push(Span::from(" "));
+        self.skip(lead.min(4));
+    }
+
+    /// next line that isn't blank is indented by 4 columns, blank lines before it stay in the MkDocs block
+    fn indented_line_follows(&self) -> bool {
+        let mut position = self.position;
+        while position < self.input.len() {
+            let line = self.rest_of_line_at(position);
+            let lead = line.iter().take_while(|t| matches!(t, Token::WhiteSpace | Token::Tab));
+            let width = lead.clone().map(|t| if *t == Token::Tab { 4 } else { 1 }).sum::<usize>();
+            if lead.count() < line.len() {
+                return width >= 4;
+            }
+            position += line.len() + 1;
+        }
+        false
+    }
+
+    /// kind of `[!NOTE]` marker that is the rest of the line
+    fn github_admonition(&self) -> Option<AdmonitionKind> {
+        match self.rest_of_line() {
+            [Token::LeftSquare, Token::Bang, Token::Indent(name), Token::RightSquare, rest @ ..]
+                if rest.iter().all(|t| *t == Token::WhiteSpace) =>
+            {
+                AdmonitionKind::github(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// kind and title of `!!! type "title"` line, title defaults to the type
+    fn mkdocs_admonition(&self) -> Option<(AdmonitionKind, String)> {
+        let [Token::Bang, Token::Bang, Token::Bang, Token::WhiteSpace, Token::Indent(name), rest @ ..] =
+            self.rest_of_line()
+        else {
+            return None;
+        };
+        let title = rest.iter().map(|t| t.literal()).collect::<String>();
+        let title = title.trim();
+        let title = match title.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+            Some(title) => title.to_string(),
+            None if title.is_empty() => {
+                let mut chars = name.chars();
+                chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+            }
+            None => return None,
+        };
+        Some((AdmonitionKind::mkdocs(name), title))
+    }
+
+    fn admonition_title(&self, kind: AdmonitionKind, title: &str) -> Span<'static> {
+        Span::styled(
+            format!("{}{}", self.options.symbols.admonition(kind), title),
+            self.style.admonition(kind).add_modifier(Modifier::BOLD),
+        )
+    }
+
+    fn nesting_error(&self) -> Error {
+        Error::at(
+            ErrorKind::NestingLimit(self.options.max_nesting),
+            self.source(self.position, self.position + 1),
+        )
+    }
+
+    /// looks for link starting at current token,
+    /// returns position of its last token, the url and style to patch the link with
+    fn link_target(&self) -> Option<(usize, String, Option<Style>)> {
+        let ext = &self.options.extensions;
+        match (&self.token, self.peek()) {
+            (Token::LeftSquare, Token::LeftSquare) if ext.wiki_links => self.wiki_link(),
+            (Token::LeftSquare, _) => self
+                .inline_link()
+                .or_else(|| self.reference_link())
+                .map(|(end, url)| (end, url, None)),
+            (Token::LeftAngle, _) => self.angle_autolink(),
+            (Token::Indent(_), _) if ext.autolinks => self.bare_autolink(),
+            _ => None,
+        }
+    }
+
+    /// `[text](url)`
+    fn inline_link(&self) -> Option<(usize, String)> {
+        let mut pos = self.position + 1;
+        while self.input.get(pos)? != &Token::RightSquare {
+            if self.input[pos].is_end() {
+                return None;
+            }
+            pos += 1;
+        }
+        // lexer maps `(` to `RightParen` and `)` to `LeftParen`
+        if self.input.get(pos + 1)? != &Token::RightParen {
+            return None;
+        }
+        pos += 2;
+        let mut url = String::new();
+        while self.input.get(pos)? != &Token::LeftParen {
+            if self.input[pos].is_end() {
+                return None;
+            }
+            url.push_str(&self.input[pos].literal());
+            pos += 1;
+        }
+        Some((pos, url))
+    }
+
+    /// `[[page]]` or `[[page|label]]`
+    fn wiki_link(&self) -> Option<(usize, String, Option<Style>)> {
+        let rest = &self.input[self.position + 2..];
+        let len = rest
+            .windows(2)
+            .take_while(|w| !w[0].is_end())
+            .position(|w| w[0] == Token::RightSquare && w[1] == Token::RightSquare)?;
+        let text = rest[..len].iter().map(|t| t.literal()).collect::<String>();
+        let page = text.split('|').next().unwrap_or_default().trim().to_string();
+        if page.is_empty() {
+            return None;
+        }
+        Some((self.position + 2 + len + 1, page, Some(self.style.link)))
+    }
+
+    /// `<https://exp.com>`
+    fn angle_autolink(&self) -> Option<(usize, String, Option<Style>)> {
+        let rest = &self.input[self.position + 1..];
+        let len = rest.iter().position(|t| {
+            t.is_end() || *t == Token::WhiteSpace || *t == Token::RightAngle || *t == Token::LeftAngle
+        })?;
+        if rest[len] != Token::RightAngle {
+            return None;
+        }
+        let url = rest[..len].iter().map(|t| t.literal()).collect::<String>();
+        if !is_url(&url) {
+            return None;
+        }
+        Some((self.position + 1 + len, url, Some(self.style.link)))
+    }
+
+    /// bare `https://exp.com` or `www.exp.com`, trailing punctuation is not part of the link
+    fn bare_autolink(&self) -> Option<(usize, String, Option<Style>)> {
+        if self.position > 0 && !matches!(self.input[self.position - 1], Token::WhiteSpace | Token::EOL) {
+            return None;
+        }
+        let rest = &self.input[self.position..];
+        let mut len = rest
+            .iter()
+            .position(|t| {
+                t.is_end()
+                    || matches!(t, Token::WhiteSpace | Token::LeftAngle | Token::RightAngle | Token::LeftParen)
+            })
+            .unwrap_or(rest.len());
+        while len > 0 && matches!(rest[len - 1], Token::Dot | Token::Colon | Token::SemiColon) {
+            len -= 1;
+        }
+        let url = rest[..len].iter().map(|t| t.literal()).collect::<String>();
+        let url = url.trim_end_matches([',', '\'', '"']).to_string();
+        if !(is_url(&url) || url.starts_with("www.") && url.len() > 4) {
+            return None;
+        }
+        // trailing comma or quote is part of the last `Indent` token
+        let end = self.position + len - 1;
+        Some((end, url, Some(self.style.link)))
+    }
+
+    /// position of the closing `~~` if current token starts strikethrough
+    fn strikethrough_end(&self, ranges: &[(usize, usize, Style)]) -> Option<usize> {
+        if !self.options.extensions.strikethrough
+            || self.token != Token::Tilde
+            || self.peek() != Token::Tilde
+            || ranges.iter().any(|r| r.0 > self.position && r.2.add_modifier.contains(Modifier::CROSSED_OUT))
+        {
+            return None;
+        }
+        let rest = &self.input[self.position + 2..];
+        rest.windows(2)
+            .take_while(|w| !w[0].is_end())
+            .position(|w| w[0] == Token::Tilde && w[1] == Token::Tilde)
+            .filter(|len| *len > 0)
+            .map(|len| self.position + 2 + len + 1)
+    }
+
+    /// position of the closing `]` if current token starts `[^note]` reference
+    fn footnote_end(&self) -> Option<usize> {
+        if !self.options.extensions.footnotes
+            || self.token != Token::LeftSquare
+            || self.peek() != Token::Caret
+        {
+            return None;
+        }
+        let rest = &self.input[self.position + 2..];
+        rest.iter()
+            .take_while(|t| !t.is_end() && **t != Token::WhiteSpace)
+            .position(|t| *t == Token::RightSquare)
+            .filter(|len| *len > 0)
+            .map(|len| self.position + 2 + len)
+    }
+
+    /// `[text][label]` or `[label][]` starting at current `[`,
+    /// returns position of the last `]` and the label
+    fn reference_label(&self) -> Option<(usize, String)> {
+        let rest = self.rest_of_line();
+        let close = rest.iter().position(|t| *t == Token::RightSquare)?;
+        if rest.get(close + 1) != Some(&Token::LeftSquare) {
+            return None;
+        }
+        let len = rest[close + 2..]
+            .iter()
+            .position(|t| *t == Token::RightSquare)?;
+        let tokens = if len == 0 {
+            &rest[1..close]
+        } else {
+            &rest[close + 2..close + 2 + len]
+        };
+        let label = tokens.iter().map(|t| t.literal()).collect::<String>();
+        if label.trim().is_empty() {
+            return None;
+        }
+        Some((self.position + close + 2 + len, label))
+    }
+
+    /// `[label]` starting at current `[` that isn't followed by `[` or `(`,
+    /// returns position of the `]` and the label
+    fn shortcut_label(&self) -> Option<(usize, String)> {
+        let rest = self.rest_of_line();
+        let close = rest.iter().position(|t| *t == Token::RightSquare)?;
+        // lexer maps `(` to `RightParen`
+        if matches!(rest.get(close + 1), Some(Token::LeftSquare | Token::RightParen))
+            || rest[1..close].contains(&Token::LeftSquare)
+        {
+            return None;
+        }
+        let label = rest[1..close].iter().map(|t| t.literal()).collect::<String>();
+        (!label.trim().is_empty()).then(|| (self.position + close, label))
+    }
+
+    /// reference link with a definition, position of the last `]` and the url
+    fn reference_link(&self) -> Option<(usize, String)> {
+        let (end, label) = self.reference_label().or_else(|| self.shortcut_label())?;
+        let url = self.definitions.get(&normalize_label(&label))?;
+        Some((end, url.clone()))
+    }
+
+    /// `[label]: url` lines of the whole input outside code blocks
+    fn read_definitions(&mut self) {
+        self.definitions.clear();
+        self.definition_lines.clear();
+        let mut start = 0;
+        let mut in_code = false;
+        // a definition can't interrupt a paragraph
+        let mut in_paragraph = false;
+        while start < self.input.len() {
+            let line = self.rest_of_line_at(start);
+            let len = line.len();
+            let fence = is_fence(line);
+            // `[^note]: text` is a footnote with `Extensions::footnotes`
+            let footnotes = self.options.extensions.footnotes;
+            let found = (!in_code && !in_paragraph)
+                .then(|| definition(line))
+                .flatten()
+                .filter(|(label, _)| !(footnotes && label.starts_with('^')));
+            let lead = line.iter().take_while(|t| **t == Token::WhiteSpace).count();
+            in_paragraph = !in_code
+                && !fence
+                && found.is_none()
+                && !matches!(line.get(lead), None | Some(Token::Heading(1..=6)));
+            if fence {
+                in_code = !in_code;
+            }
+            if let Some((label, url)) = found {
+                self.definitions
+                    .entry(normalize_label(&label))
+                    .or_insert(url);
+                self.definition_lines.push(start);
+            }
+            start += len + 1;
+        }
+    }
+
+    /// position of the last backtick of the run that closes the code span opened at current token,
+    /// the closing run has as many backticks as the opening one
+    fn code_span_end(&self) -> Option<usize> {
+        let rest = self.rest_of_line();
+        let ticks = rest.iter().take_while(|t| **t == Token::BackTick).count();
+        let mut i = ticks;
+        while i < rest.len() {
+            let run = rest[i..].iter().take_while(|t| **t == Token::BackTick).count();
+            if run == ticks {
+                return Some(self.position + i + run - 1);
+            }
+            i += run.max(1);
+        }
+        None
+    }
+
+    /// replaces tabs with spaces up to the next tab stop before the lines are read, every
+    /// `TAB_STOP` columns for block structure and `ParseOptions::tab_width` inside code blocks
+    fn expand_tabs(&mut self) {
+        let mut start = 0;
+        let mut in_code = false;
+        while start < self.input.len() {
+            let width = if in_code { self.options.tab_width.max(1) } else { TAB_STOP };
+            self.expand_line_tabs(start, width);
+            let line = self.rest_of_line_at(start);
+            if is_fence(line) {
+                in_code = !in_code;
+            }
+            start += line.len() + 1;
+        }
+    }
+
+    /// replaces tabs of the line starting at token `start` with spaces, tab stops are `width` apart
+    fn expand_line_tabs(&mut self, start: usize, width: usize) {
+        let end = start + self.rest_of_line_at(start).len();
+        if !self.input[start..end].contains(&Token::Tab) {
+            return;
+        }
+
+        let mut tokens = Vec::new();
+        let mut sources = Vec::new();
+        let mut column = 0;
+        for (i, token) in self.input[start..end].iter().enumerate() {
+            let n = match token {
+                Token::Tab => width - column % width,
+                _ => 1,
+            };
+            let expanded = match token {
+                Token::Tab => Token::WhiteSpace,
+                token => token.clone(),
+            };
+            column += n * expanded.literal().chars().count();
+            tokens.extend(std::iter::repeat_n(expanded, n));
+            if let Some(source) = self.sources.get(start + i) {
+                sources.extend(std::iter::repeat_n(*source, n));
+            }
+        }
+        if self.sources.len() >= end {
+            self.sources.splice(start..end, sources);
+        }
+        self.input.splice(start..end, tokens);
+    }
+
+    fn diagnose(&mut self, kind: DiagnosticKind, line: usize, from: usize, to: usize) {
+        let source = self.source(from, to);
+        self.diagnostics.push(MdDiagnostic { kind, line, source });
+    }
+
+    /// reports reference link at current token without definition and bare urls
+    fn check_references(&mut self) {
+        let position = self.position;
+        if self.token == Token::LeftSquare && self.inline_link().is_none() {
+            if let Some((end, label)) = self.reference_label() {
+                if !self.definitions.contains_key(&normalize_label(&label)) {
+                    let kind = DiagnosticKind::UnresolvedReference(label);
+                    self.diagnose(kind, self.lines.len(), position, end + 1);
+                }
+            }
+        }
+        if let Token::Indent(_) = self.token {
+            if let Some((end, url, _)) = self.bare_autolink() {
+                self.diagnose(
+                    DiagnosticKind::BareUrl(url),
+                    self.lines.len(),
+                    position,
+                    end + 1,
+                );
+            }
+        }
+    }
+
+    /// checks cell count of table rows against the header
+    fn check_table(&mut self) {
+        let rest = self.rest_of_line();
+        let row = rest
+            .iter()
+            .skip_while(|t| **t == Token::WhiteSpace)
+            .cloned()
+            .collect::<Vec<_>>();
+        if !self.options.extensions.tables || self.in_code || row.first() != Some(&Token::Pipe) {
+            self.table = None;
+            return;
+        }
+        let len = rest.len();
+        let mut row = row;
+        while row.last() == Some(&Token::WhiteSpace) {
+            row.pop();
+        }
+        let pipes = row
+            .iter()
+            .enumerate()
+            .filter(|(i, t)| **t == Token::Pipe && (*i == 0 || row[i - 1] != Token::BackSlash))
+            .count();
+        // rows start with a pipe, a closing pipe does not start another cell
+        let cells = pipes - usize::from(row.len() > 1 && row.last() == Some(&Token::Pipe));
+        let delimiter = row.contains(&Token::Dash)
+            && row.iter().all(|t| {
+                matches!(
+                    t,
+                    Token::Pipe | Token::Dash | Token::Colon | Token::WhiteSpace
+                )
+            });
+
+        let line = self.lines.len();
+        let position = self.position;
+        match self.table {
+            None => self.table = Some((cells, false, position, line)),
+            Some((_, false, header, header_line)) if !delimiter => {
+                let end = header + self.rest_of_line_at(header).len();
+                self.diagnose(
+                    DiagnosticKind::MissingTableDelimiter,
+                    header_line,
+                    header,
+                    end,
+                );
+                self.table = Some((0, true, header, header_line));
+            }
+            Some((expected, seen, header, header_line)) => {
+                if expected > 0 && cells != expected {
+                    let kind = DiagnosticKind::TableCells {
+                        expected,
+                        found: cells,
+                    };
+                    self.diagnose(kind, line, position, position + len);
+                }
+                if !seen {
+                    self.table = Some((expected, true, header, header_line));
+                }
+            }
+        }
+    }
+
+    /// tracks emphasis delimiters of the rest of the line,
+    /// the ones still open at the end of the paragraph are reported
+    fn check_emphasis(&mut self) {
+        let rest = self.rest_of_line().to_vec();
+        let heading = matches!(rest.first(), Some(Token::Heading(1..=6)));
+        if self.in_code || heading || rest.iter().all(|t| *t == Token::WhiteSpace) {
+            self.close_paragraph();
+        }
+        if self.in_code {
+            return;
+        }
+
+        let space = |t: Option<&Token>| t.is_none_or(|t| *t == Token::WhiteSpace);
+        let mut runs = Vec::new();
+        let mut in_span = false;
+        let mut i = 0;
+        while i < rest.len() {
+            let token = &rest[i];
+            if *token == Token::BackTick {
+                in_span = !in_span;
+            }
+            if in_span || !matches!(token, Token::Asterisk | Token::Undersocre) {
+                i += 1;
+                continue;
+            }
+            let len = rest[i..].iter().take_while(|t| *t == token).count();
+            let before = i.checked_sub(1).map(|j| &rest[j]);
+            let after = rest.get(i + len);
+            let intraword = *token == Token::Undersocre
+                && matches!(before, Some(Token::Indent(_)))
+                && matches!(after, Some(Token::Indent(_)));
+            if !intraword {
+                runs.push((
+                    token.clone(),
+                    self.position + i,
+                    len,
+                    !space(after),
+                    !space(before),
+                ));
+            }
+            i += len;
+        }
+
+        let line = self.lines.len();
+        for (token, position, len, opens, closes) in runs {
+            if closes {
+                if let Some(k) = self.emphasis.iter().rposition(|e| e.0 == token) {
+                    self.emphasis.remove(k);
+                    continue;
+                }
+            }
+            if opens {
+                self.emphasis.push((token, position, len, line));
+            }
+        }
+        if heading {
+            self.close_paragraph();
+        }
+    }
+
+    fn close_paragraph(&mut self) {
+        for (token, position, len, line) in std::mem::take(&mut self.emphasis) {
+            let kind = DiagnosticKind::UnclosedEmphasis(token.literal().repeat(len));
+            self.diagnose(kind, line, position, position + len);
+        }
+    }
+
+    /// stores heading starting at current token, with plain text of the rest of the line
+    fn read_heading(&mut self, level: usize) {
+        let mut tokens = self.input[self.position + 1..]
+            .iter()
+            .take_while(|t| !t.is_end())
+            .collect::<Vec<_>>();
+        while tokens.last() == Some(&&Token::WhiteSpace) {
+            tokens.pop();
+        }
+        // closing `#`s need a space before them, `# C#` is about C#
+        if let [.., Token::WhiteSpace, Token::Heading(_)] = tokens[..] {
+            tokens.pop();
+        }
+        let tokens = tokens.into_iter().cloned().collect::<Vec<_>>();
+        let text = self.plain_text(&tokens).trim().to_string();
+
+        let base = slug(&text);
+        let mut anchor = base.clone();
+        let mut n = 0;
+        if self.slugs.contains_key(&base) {
+            n = self.slugs[&base];
+            while self.slugs.contains_key(&anchor) {
+                n += 1;
+                anchor = format!("{}-{}", base, n);
+            }
+            self.slugs.insert(base.clone(), n);
+        }
+        self.slugs.entry(anchor.clone()).or_insert(0);
+
+        let end = self.position + self.rest_of_line().len();
+        if n > 0 {
+            self.diagnose(
+                DiagnosticKind::DuplicateAnchor(base),
+                self.lines.len(),
+                self.position,
+                end,
+            );
+        }
+        self.headings.push(MdHeading {
+            level,
+            text,
+            slug: anchor,
+            line: self.lines.len(),
+            source: self.source(self.position, end),
+        });
+    }
+
+    /// text of inline `tokens` without markup, links are their text, code spans their content,
+    /// escapes and entities are decoded, html and emphasis delimiters are left out
+    fn plain_text(&self, tokens: &[Token]) -> String {
+        let delimiters = emphasis_delimiters(tokens);
+        let mut text = String::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Token::BackTick => {
+                    let ticks = tokens[i..].iter().take_while(|t| **t == Token::BackTick).count();
+                    let mut end = i + ticks;
+                    while end < tokens.len() {
+                        let run = tokens[end..].iter().take_while(|t| **t == Token::BackTick).count();
+                        if run == ticks {
+                            break;
+                        }
+                        end += run.max(1);
+                    }
+                    if end < tokens.len() {
+                        text.extend(tokens[i + ticks..end].iter().map(|t| t.literal()));
+                        i = end + ticks;
+                    } else {
+                        text.push_str(&"`".repeat(ticks));
+                        i += ticks;
+                    }
+                    continue;
+                }
+                Token::LeftSquare => {
+                    if let Some((inner, end)) = self.link_text(&tokens[i..]) {
+                        text.push_str(&self.plain_text(&tokens[i + inner.start..i + inner.end]));
+                        i += end + 1;
+                        continue;
+                    }
+                    text.push('[');
+                }
+                // `![alt](src)` is its alt text
+                Token::Bang
+                    if tokens.get(i + 1) == Some(&Token::LeftSquare)
+                        && self.link_text(&tokens[i + 1..]).is_some() => {}
+                Token::Escaped(c) => text.push(*c),
+                Token::Entity(e) => text.push_str(&entities::decode(e).unwrap_or(e.clone())),
+                Token::Html(_) => {}
+                Token::Emoji(code) if self.options.extensions.emoji => {
+                    let ascii = self.options.emoji_mode == EmojiMode::Ascii;
+                    text.push_str(&emoji::expand(code, ascii).unwrap_or(code.clone()));
+                }
+                Token::Asterisk | Token::Undersocre if delimiters.contains(&i) => {}
+                token => text.push_str(&token.literal()),
+            }
+            i += 1;
+        }
+        text
+    }
+
+    /// tokens of the text and position of the last token of `[text](url)`, `[text][label]`,
+    /// `[label]` or `[[page|text]]` at the start of `tokens`
+    fn link_text(&self, tokens: &[Token]) -> Option<(std::ops::Range<usize>, usize)> {
+        let close = |from: usize, token: Token| {
+            tokens[from..]
+                .iter()
+                .position(|t| *t == token)
+                .map(|p| from + p)
+        };
+        if self.options.extensions.wiki_links && tokens.get(1) == Some(&Token::LeftSquare) {
+            let end = (2..tokens.len()).find(|i| {
+                tokens[*i - 1] == Token::RightSquare && tokens[*i] == Token::RightSquare
+            })?;
+            let start = close(2, Token::Pipe).filter(|p| *p < end).map_or(2, |p| p + 1);
+            return Some((start..end - 1, end));
+        }
+        let text_end = close(1, Token::RightSquare)?;
+        match tokens.get(text_end + 1) {
+            // lexer maps `(` to `RightParen` and `)` to `LeftParen`
+            Some(Token::RightParen) => Some((1..text_end, close(text_end + 2, Token::LeftParen)?)),
+            Some(Token::LeftSquare) => {
+                let end = close(text_end + 2, Token::RightSquare)?;
+                let label = if end == text_end + 2 {
+                    &tokens[1..text_end]
+                } else {
+                    &tokens[text_end + 2..end]
+                };
+                let label = label.iter().map(|t| t.literal()).collect::<String>();
+                self.definitions
+                    .contains_key(&normalize_label(&label))
+                    .then_some((1..text_end, end))
+            }
+            // shortcut `[label]`
+            _ => {
+                let label = tokens[1..text_end].iter().map(|t| t.literal()).collect::<String>();
+                self.definitions
+                    .contains_key(&normalize_label(&label))
+                    .then_some((1..text_end, text_end))
+            }
+        }
+    }
+
+    /// location of tokens `from..to`, default if token locations are unknown
+    fn source(&self, from: usize, to: usize) -> SourceSpan {
+        let at = |pos: usize| match self.sources.get(pos) {
+            Some(s) => *s,
+            // end of input, right after the last token
+            None => match (self.sources.last(), self.input.last()) {
+                (Some(last), Some(Token::EOL)) => SourceSpan {
+                    start: last.end,
+                    end: last.end,
+                    line: last.line + 1,
+                    column: 0,
+                },
+                (Some(last), Some(token)) => SourceSpan {
+                    start: last.end,
+                    end: last.end,
+                    line: last.line,
+                    column: last.column + token.literal().chars().count(),
+                },
+                _ => SourceSpan::default(),
+            },
+        };
+        let start = at(from);
+        SourceSpan {
+            end: at(to).start.max(start.start),
+            ..start
+        }
+    }
+
     fn read_token(&mut self) {
         if self.read_position >= self.input.len() {
             self.token = Token::EOF;
@@ -143,20 +1365,143 @@ impl Parser {
         self.read_position += 1;
     }
 
-    fn peek(&mut self) -> Token {
+    fn peek(&self) -> Token {
         if self.read_position >= self.input.len() {
-            return Token::EOF;
+            Token::EOF
         } else {
             self.input[self.read_position].clone()
         }
     }
 }
 
+/// maps characters to their subscript or superscript form
+type Script = fn(char) -> Option<char>;
+
+/// columns between tab stops outside code blocks, as in CommonMark
+const TAB_STOP: usize = 4;
+
+/// label and url of `[label]: url` line
+fn definition(line: &[Token]) -> Option<(String, String)> {
+    let lead = line.iter().take_while(|t| **t == Token::WhiteSpace).count();
+    let line = &line[lead..];
+    if lead > 3 || line.first() != Some(&Token::LeftSquare) {
+        return None;
+    }
+    let close = line.iter().position(|t| *t == Token::RightSquare)?;
+    if close < 2
+        || line[1..close].contains(&Token::LeftSquare)
+        || line.get(close + 1) != Some(&Token::Colon)
+    {
+        return None;
+    }
+    let label = line[1..close]
+        .iter()
+        .map(|t| t.literal())
+        .collect::<String>();
+    let rest = line[close + 2..]
+        .iter()
+        .map(|t| t.literal())
+        .collect::<String>();
+    let (url, title) = rest.trim().split_once([' ', '\t']).unwrap_or((rest.trim(), ""));
+    // the only thing after the url can be a title quoted on the same line
+    let title = title.trim();
+    let quoted = [('"', '"'), ('\'', '\''), ('(', ')')]
+        .iter()
+        .any(|&(open, close)| title.len() > 1 && title.starts_with(open) && title.ends_with(close));
+    let url = url.trim_start_matches('<').trim_end_matches('>');
+    (!label.trim().is_empty() && !url.is_empty() && (title.is_empty() || quoted))
+        .then(|| (label, url.to_string()))
+}
+
+/// line is a backtick code fence, its info string can't contain backticks
+fn is_fence(line: &[Token]) -> bool {
+    let lead = line.iter().take_while(|t| **t == Token::WhiteSpace).count();
+    let ticks = line[lead..].iter().take_while(|t| **t == Token::BackTick).count();
+    ticks >= 3 && !line[lead + ticks..].iter().any(|t| t.literal().contains('`'))
+}
+
+/// positions of `*` and `_` in `tokens` that open or close emphasis, delimiters without a partner,
+/// `_` inside words and the ones in code spans are text
+fn emphasis_delimiters(tokens: &[Token]) -> Vec<usize> {
+    let space = |t: Option<&Token>| t.is_none_or(|t| *t == Token::WhiteSpace);
+    let mut open: Vec<(&Token, usize, usize)> = Vec::new();
+    let mut delimiters = Vec::new();
+    let mut in_span = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        if *token == Token::BackTick {
+            in_span = !in_span;
+        }
+        if in_span || !matches!(token, Token::Asterisk | Token::Undersocre) {
+            i += 1;
+            continue;
+        }
+        let len = tokens[i..].iter().take_while(|t| *t == token).count();
+        let before = i.checked_sub(1).map(|j| &tokens[j]);
+        let after = tokens.get(i + len);
+        let intraword = *token == Token::Undersocre
+            && matches!(before, Some(Token::Indent(_)))
+            && matches!(after, Some(Token::Indent(_)));
+        if !intraword {
+            let opener = open.iter().rposition(|o| o.0 == token);
+            match opener {
+                Some(k) if !space(before) => {
+                    let (_, start, opening) = open.remove(k);
+                    delimiters.extend(start..start + opening);
+                    delimiters.extend(i..i + len);
+                }
+                _ if !space(after) => open.push((token, i, len)),
+                _ => {}
+            }
+        }
+        i += len;
+    }
+    delimiters
+}
+
+/// case insensitive label with collapsed whitespace
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn is_url(url: &str) -> bool {
+    ["http://", "https://", "mailto:", "ftp://"]
+        .iter()
+        .any(|scheme| url.len() > scheme.len() && url.starts_with(scheme))
+}
+
+fn line_width(spans: &[Span]) -> usize {
+    spans.iter().map(|s| s.width()).sum()
+}
+
+/// `$tex$`, `$$tex$$` or a line of a math block drawn with unicode symbols,
+/// tex as written when it can't be converted, `$$` block delimiters are hidden
+fn math_span(tex: &str, style: &MdStyle, max_depth: usize) -> Span<'static> {
+    let inner = match tex.strip_prefix("$$").and_then(|t| t.strip_suffix("$$")) {
+        Some(inner) => inner,
+        None if tex.trim() == "$$" => "",
+        None => tex.strip_prefix('$').and_then(|t| t.strip_suffix('$')).unwrap_or(tex),
+    };
+    let text = math::to_unicode(inner, max_depth).unwrap_or_else(|| inner.trim().to_string());
+    Span::styled(text, style.math)
+}
+
 #[cfg(test)]
 mod test {
 
-    use crate::parser::lexer::Lexer;
+    use crate::{
+        document::document::{DiagnosticKind, MdLink, SourceSpan},
+        options::options::{EmojiMode, HtmlMode, ParseOptions},
+        parser::lexer::Lexer,
+        style::{style::MdStyle, symbols::MdSymbols},
+    };
     use anyhow::{Ok, Result};
+    use ratatui::style::{Color, Modifier, Style};
 
     use super::Parser;
 
@@ -179,16 +1524,507 @@ abc
 
         let mut parser = Parser::new(res, None);
         let res = parser.parse()?;
-        //println!("{:#?}", res);
-        let a = res.iter().map(|f| {
-            f.spans
-                .iter()
-                .map(|f| format!("{}", f.content.to_string()))
-                .collect::<Vec<String>>().join("")
-        }).collect::<String>();
-        println!("{:?}",a);
+        let lines = res.into_iter().map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(lines, md.lines().collect::<Vec<_>>());
+        Ok(())
+    }
+
+    fn render(md: &str, symbols: MdSymbols) -> Result<Vec<String>> {
+        let mut lexer = Lexer::new();
+        let res = lexer.parse::<&str>(&md)?;
+
+        let mut parser = Parser::new(res, None);
+        parser.options = ParseOptions::gfm().symbols(symbols);
+        Ok(parser.parse()?.into_iter().map(String::from).collect())
+    }
+
+    #[test]
+    fn first_and_last_line() -> Result<()> {
+        assert!(render("", MdSymbols::raw())?.is_empty());
+        assert_eq!(render("\n", MdSymbols::raw())?, vec![""]);
+        assert_eq!(render("# a\nb", MdSymbols::raw())?, vec!["# a", "b"]);
+        assert_eq!(render("a\r\n\r\nb\r\n", MdSymbols::raw())?, vec!["a", "", "b"]);
+        assert_eq!(render("a\rb\r\rc", MdSymbols::raw())?, vec!["a", "b", "", "c"]);
+
+        let parser = parse_with("# first\n[last](#first)", ParseOptions::default())?;
+        assert_eq!(parser.headings[0].line, 0);
+        assert_eq!(parser.links[0].line, 1);
+        Ok(())
+    }
+
+    fn parse_with(md: &str, options: ParseOptions) -> Result<Parser> {
+        let mut lexer = Lexer::with_options(options.clone());
+        let res = lexer.parse::<&str>(&md)?;
+
+        let mut parser = Parser::new(res, None);
+        parser.sources = lexer.sources;
+        parser.options = options;
+        parser.parse()?;
+        Ok(parser)
+    }
+
+    #[test]
+    fn extensions() -> Result<()> {
+        let md = "see https://exp.com/a. and <http://b.org> or [[Home|home page]]
+a ~~gone~~ b[^1]
+- [x] done
+| a | b |
+|---|:-:|
+";
+        let gfm = parse_with(md, ParseOptions::gfm().wiki_links(true))?;
+        let urls = gfm.links.iter().map(|l| l.url.as_str()).collect::<Vec<_>>();
+        assert_eq!(urls, vec!["https://exp.com/a", "http://b.org", "Home"]);
+        assert_eq!((gfm.links[0].start, gfm.links[0].end), (4, 21));
+
+        let line = &gfm.lines[1];
+        let crossed = line
+            .spans
+            .iter()
+            .filter(|s| s.style.add_modifier.contains(Modifier::CROSSED_OUT))
+            .map(|s| s.content.as_ref())
+            .collect::<String>();
+        assert_eq!(crossed, "~~gone~~");
+        assert_eq!(line.spans.last().map(|s| s.style), Some(gfm.style.link_text));
+        assert_eq!(gfm.lines[3].spans[0].style, gfm.style.table);
+        assert_eq!(gfm.lines[4].spans[0].style, gfm.style.table);
+
+        let cm = parse_with(md, ParseOptions::commonmark())?;
+        assert_eq!(ParseOptions::default(), ParseOptions::gfm());
+        let urls = cm.links.iter().map(|l| l.url.as_str()).collect::<Vec<_>>();
+        assert_eq!(urls, vec!["http://b.org"]);
+        assert!(cm.lines[1]
+            .spans
+            .iter()
+            .all(|s| !s.style.add_modifier.contains(Modifier::CROSSED_OUT)));
+        assert_eq!(String::from(cm.lines[2].clone()), "- [x] done");
+        Ok(())
+    }
+
+    #[test]
+    fn symbols() -> Result<()> {
+        let md = "## Title
+- [ ] one
+  * [x] two
+> quote
+***
+```rust
+# not heading
+```
+[a](b)
+";
+        assert_eq!(render(md, MdSymbols::raw())?, md.lines().collect::<Vec<_>>());
+        assert_eq!(
+            render(md, MdSymbols::unicode())?,
+            vec![
+                "◎ Title",
+                "• ☐ one",
+                "  ◦ ☑ two",
+                "│ quote",
+                &"─".repeat(40),
+                "╭── rust",
+                "│ # not heading",
+                "╰──",
+                "↗[a](b)",
+            ]
+        );
+        assert_eq!(render("- a\n---\n", MdSymbols::ascii())?[0], "* a");
+        let md = "- a\n    - four\n\t- tab\n        - eight\n\n      more\n- b\n  1. one\n     - in one\nlazy\n\ntext\n  - alone\n";
+        assert_eq!(
+            render(md, MdSymbols::unicode())?,
+            vec![
+                "• a",
+                "    ◦ four",
+                "    ◦ tab",
+                "        ▪ eight",
+                "",
+                "      more",
+                "• b",
+                "  1. one",
+                "     ▪ in one",
+                "lazy",
+                "",
+                "text",
+                "  • alone",
+            ]
+        );
+        assert_eq!(render("``` ```\naaa\n", MdSymbols::unicode())?, vec!["``` ```", "aaa"]);
+        Ok(())
+    }
+
+    #[test]
+    fn collects_links() -> Result<()> {
+        let md = "# links
+see [docs](https://exp.com/a) and [b](c)
+[broken](nope
+";
+
+        let mut lexer = Lexer::new();
+        let res = lexer.parse::<&str>(&md)?;
+
+        let mut parser = Parser::new(res, None);
+        parser.sources = lexer.sources;
+        parser.parse()?;
+
+        let source = |start, end, column| SourceSpan { start, end, line: 1, column };
+        assert_eq!(
+            parser.links,
+            vec![
+                MdLink {
+                    url: "https://exp.com/a".into(),
+                    line: 1,
+                    start: 4,
+                    end: 29,
+                    target: None,
+                    source: source(12, 37, 4),
+                },
+                MdLink {
+                    url: "c".into(),
+                    line: 1,
+                    start: 34,
+                    end: 40,
+                    target: None,
+                    source: source(42, 48, 34),
+                },
+            ]
+        );
+        assert_eq!(parser.line_sources[1], source(8, 48, 0));
+        assert_eq!(parser.line_sources[2].end, md.len() - 1);
+
+        // definitions are not drawn and their urls are no bare links
+        let md = "[a][r] and [b][]\n\n[r]: https://exp.com/r\n [b]: https://exp.com/b\nend\n";
+        let parser = parse_with(md, ParseOptions::gfm())?;
+        let lines = parser.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(lines, vec!["[a][r] and [b][]", "", "end"]);
+        let links = parser.links.iter().map(|l| (l.line, l.url.as_str())).collect::<Vec<_>>();
+        assert_eq!(links, vec![(0, "https://exp.com/r"), (0, "https://exp.com/b")]);
+        assert_eq!(parser.line_sources[2].line, 4);
+        // shortcut references need a definition and are no diagnostic without one
+        let md = "# See [foo]\n[foo] and [bar] [x](y) [z][foo]\n\n[foo]: /u\n";
+        let parser = parse_with(md, ParseOptions::gfm())?;
+        let links = parser.links.iter().map(|l| (l.line, l.url.as_str())).collect::<Vec<_>>();
+        assert_eq!(links, vec![(0, "/u"), (1, "/u"), (1, "y"), (1, "/u")]);
+        assert_eq!(parser.headings[0].text, "See foo");
+        assert!(parser.diagnostics.is_empty());
+        // footnotes are drawn
+        let parser = parse_with("a[^1]\n\n[^1]: note\n", ParseOptions::gfm())?;
+        assert_eq!(String::from(parser.lines[2].clone()), "[^1]: note");
+
+        // links in code spans and code blocks are text
+        let md = "`[a](b)` [e](f) `<http://g>`\n```\n[c](d) https://x.y\n```\n";
+        let parser = parse_with(md, ParseOptions::gfm())?;
+        let links = parser.links.iter().map(|l| (l.line, l.url.as_str())).collect::<Vec<_>>();
+        assert_eq!(links, vec![(0, "f")]);
+        assert_eq!(String::from(parser.lines[2].clone()), "[c](d) https://x.y");
+        Ok(())
+    }
+
+    #[test]
+    fn collects_headings() -> Result<()> {
+        let md = "# Getting Started
+text #not heading
+#ABC
+## Setup **fast** ##
+## Setup fast
+";
+
+        let mut lexer = Lexer::new();
+        let res = lexer.parse::<&str>(&md)?;
+
+        let mut parser = Parser::new(res, None);
+        parser.parse()?;
+
+        let headings = parser
+            .headings
+            .iter()
+            .map(|h| (h.level, h.text.as_str(), h.slug.as_str(), h.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headings,
+            vec![
+                (1, "Getting Started", "getting-started", 0),
+                (2, "Setup fast", "setup-fast", 3),
+                (2, "Setup fast", "setup-fast-1", 4),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn diagnostics() -> Result<()> {
+        let md = "# Intro
+# Intro
+see [docs][guide] and [home][] or [x][def], *open
+still **open** at https://exp.com `[a][missing] https://b.c`
+
+[def]: https://exp.com/def
+
+| a | b |
+| c | d |
+| e |
+
+```rust
+let a = *b;
+";
+        let parser = parse_with(md, ParseOptions::gfm())?;
+        let found = parser
+            .diagnostics
+            .iter()
+            .map(|d| (d.kind.clone(), d.line, d.source.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    DiagnosticKind::DuplicateAnchor("intro".into()),
+                    1,
+                    "2:1".into()
+                ),
+                (
+                    DiagnosticKind::UnresolvedReference("guide".into()),
+                    2,
+                    "3:5".into()
+                ),
+                (
+                    DiagnosticKind::UnresolvedReference("home".into()),
+                    2,
+                    "3:23".into()
+                ),
+                (
+                    DiagnosticKind::UnclosedEmphasis("*".into()),
+                    2,
+                    "3:45".into()
+                ),
+                (
+                    DiagnosticKind::BareUrl("https://exp.com".into()),
+                    3,
+                    "4:19".into()
+                ),
+                (DiagnosticKind::MissingTableDelimiter, 6, "8:1".into()),
+                (DiagnosticKind::UnclosedFence, 10, "12:1".into()),
+            ]
+        );
+        assert_eq!(parser.links[0].url, "https://exp.com/def");
+        assert_eq!(parser.links[0].line, 2);
+
+        let md = "| a | b |\n|---|---|\n| c |\n| d | e | f |\n\n_snake_case_ and 2 * 3\n";
+        let parser = parse_with(md, ParseOptions::gfm())?;
+        let found = parser
+            .diagnostics
+            .iter()
+            .map(|d| d.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                DiagnosticKind::TableCells {
+                    expected: 2,
+                    found: 1
+                },
+                DiagnosticKind::TableCells {
+                    expected: 2,
+                    found: 3
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tabs() -> Result<()> {
+        let md = "-\tone\n\t- two\n>\tquote\nab\tc\n```\n\tx\ty\n```\n";
+        let options = ParseOptions::default().symbols(MdSymbols::raw());
+        let parser = parse_with(md, options.clone())?;
+        let lines = parser.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec!["-   one", "    - two", ">   quote", "ab  c", "```", "    x   y", "```"]
+        );
+        assert_eq!(parser.line_sources.len(), parser.lines.len());
+        assert_eq!(parser.line_sources[3].range(), 21..25);
+
+        let parser = parse_with(md, options.clone().tab_width(2))?;
+        let lines = parser.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(lines[5], "  x y");
+
+        // tabs are spaces before definitions are read, a line of tabs is blank
+        let parser = parse_with("[a]\n\t\n[a]:\t/u\n", options)?;
+        let lines = parser.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(lines, vec!["[a]", "    "]);
+        assert_eq!(parser.links[0].url, "/u");
+        Ok(())
+    }
+
+    #[test]
+    fn escapes_and_entities() -> Result<()> {
+        let md = "\\*not emphasis\\* \\# \\{x\\}\nTom &amp; Jerry&nbsp;&copy; &#x1F600; &nope; a & b\n`\\* &amp;` ``a ` \\*`` \\*\n```\n\\* &amp;\n```\n";
+        let lines = render(md, MdSymbols::raw())?;
+        assert_eq!(
+            lines,
+            vec![
+                "*not emphasis* # {x}",
+                "Tom & Jerry\u{a0}© 😀 &nope; a & b",
+                "`\\* &amp;` ``a ` \\*`` *",
+                "```",
+                "\\* &amp;",
+                "```",
+            ]
+        );
+        let parser = parse_with(md, ParseOptions::default())?;
+        assert!(parser.diagnostics.is_empty());
+
+        // a backtick inside a tag still keeps the line from being a fence
+        let md = "What? yes, café 100% done {ok} me@x.com\n&#1;&NewLine;&Tab;\n```<b title=\"`\">\n&amp;\n";
+        assert_eq!(
+            render(md, MdSymbols::raw())?,
+            vec!["What? yes, café 100% done {ok} me@x.com", "\u{fffd}  ", "```", "&"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn html() -> Result<()> {
+        let md = "<details>\n<summary>More</summary>\n\npress <kbd>Ctrl</kbd>, H<sub>2</sub>O x<sup>2</sup> <b>bold <i>both</i></b>\none<br>two <br/>[link](x)<br>\n<!-- hidden\nstill hidden --> shown <span>x</span>\n`<b>code</b>`\n</details>\n";
+        let lines = |mode| -> Result<Vec<String>> {
+            let options = ParseOptions::default().html(mode).symbols(MdSymbols::unicode());
+            let parser = parse_with(md, options)?;
+            Ok(parser.lines.iter().cloned().map(String::from).collect())
+        };
+        assert_eq!(
+            lines(HtmlMode::Interpret)?,
+            vec![
+                "",
+                "▸ More",
+                "",
+                "press Ctrl, H₂O x² bold both",
+                "one",
+                "two ",
+                "↗[link](x)",
+                "",
+                " shown x",
+                "`<b>code</b>`",
+                "",
+            ]
+        );
+        assert_eq!(lines(HtmlMode::Strip)?[1], "More");
+        assert_eq!(lines(HtmlMode::Dim)?[4], "one<br>two <br/>↗[link](x)<br>");
+
+        let options = ParseOptions::default().html(HtmlMode::Interpret);
+        let parser = parse_with(md, options)?;
+        let line = &parser.lines[3];
+        let style_of = |text: &str| line.spans.iter().find(|s| s.content == text).map(|s| s.style);
+        assert_eq!(style_of("Ctrl"), Some(parser.style.text.patch(parser.style.tag)));
+        assert!(style_of("both").unwrap().add_modifier.contains(Modifier::BOLD | Modifier::ITALIC));
+        let link = &parser.links[0];
+        assert_eq!((link.line, link.start, link.end), (6, 0, 9));
+        assert_eq!(parser.line_sources[6], parser.line_sources[4]);
+
+        let parser = parse_with("x [a<br>b](y) z\n", ParseOptions::default())?;
+        let lines = parser.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(lines, vec!["x [a", "b](y) z"]);
+        let links = parser.links.iter().map(|l| (l.line, l.start, l.end, l.url.as_str())).collect::<Vec<_>>();
+        assert_eq!(links, vec![(0, 2, 4, "y"), (1, 0, 5, "y")]);
+        Ok(())
+    }
+
+    #[test]
+    fn admonitions() -> Result<()> {
+        let md = "> [!WARNING]\n> Mind the gap!\n\n!!! danger \"Hot\"\n    Don't touch.\n\n    Really.\n\nafter\n> [!nope]\n";
+        let options = ParseOptions::gfm().mkdocs_admonitions(true).symbols(MdSymbols::unicode());
+        let parser = parse_with(md, options)?;
+        let lines = parser.lines.iter().cloned().map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "│ ⚠ Warning",
+                "│ Mind the gap!",
+                "",
+                "│ ✖ Hot",
+                "│ Don't touch.",
+                "│ ",
+                "│ Really.",
+                "",
+                "after",
+                "│ [!nope]",
+            ]
+        );
+        let warning = parser.style.warning;
+        assert_eq!(parser.lines[0].spans[0].style, warning);
+        assert_eq!(parser.lines[0].spans[2].style, warning.add_modifier(Modifier::BOLD));
+        assert_eq!(parser.lines[1].spans[0].style, warning);
+        assert_eq!(parser.lines[4].spans[0].style, parser.style.caution);
+        assert_eq!(parser.lines[9].spans[0].style, parser.style.blocqoutes);
+
+        let commonmark = parse_with(md, ParseOptions::commonmark())?;
+        assert_eq!(String::from(commonmark.lines[0].clone()), "> [!WARNING]");
+        let gfm = parse_with(md, ParseOptions::gfm())?;
+        assert_eq!(String::from(gfm.lines[0].clone()), "> Warning");
+        assert_eq!(String::from(gfm.lines[3].clone()), "!!! danger \"Hot\"");
+        Ok(())
+    }
+
+    #[test]
+    fn emoji() -> Result<()> {
+        let md = "ship :rocket: :warning: `:tada:` :nope:\n";
+        let line = |options: ParseOptions| -> Result<String> {
+            Ok(String::from(parse_with(md, options)?.lines[0].clone()))
+        };
+        assert_eq!(line(ParseOptions::gfm().emoji(true))?, "ship 🚀 ⚠️ `:tada:` :nope:");
+        let ascii = ParseOptions::gfm().emoji(true).emoji_mode(EmojiMode::Ascii);
+        assert_eq!(line(ascii)?, "ship :rocket: /!\\ `:tada:` :nope:");
+        assert_eq!(line(ParseOptions::gfm())?, "ship :rocket: :warning: `:tada:` :nope:");
+        Ok(())
+    }
+
+    #[test]
+    fn nested_styles() -> Result<()> {
+        let mut lexer = Lexer::new();
+        let tokens = lexer.parse::<&str>(&"<b>[a](b) [[c]]</b> <i><b>d</b></i>")?;
+        let style = MdStyle::builder()
+            .text(Style::default().fg(Color::White))
+            .link(Style::default().fg(Color::Blue))
+            .build();
+        let mut parser = Parser::new(tokens, Some(style));
+        parser.options = ParseOptions::gfm().wiki_links(true);
+        parser.parse()?;
+        let line = &parser.lines[0];
+        let style_of = |text: &str| line.spans.iter().find(|s| s.content == text).map(|s| s.style);
+        let link = Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD);
+        assert_eq!(style_of("("), Some(link));
+        assert_eq!(style_of(")"), Some(link));
+        assert_eq!(style_of("c").map(|s| (s.fg, s.add_modifier)), Some((link.fg, Modifier::BOLD)));
+        let both = style_of("d").unwrap();
+        assert_eq!((both.fg, both.add_modifier), (Some(Color::White), Modifier::BOLD | Modifier::ITALIC));
+        Ok(())
+    }
+
+    #[test]
+    fn math() -> Result<()> {
+        let md = "area $\\pi r^2$ costs $5 `$x$`\n$$\n\\frac{a}{b} \\le \\alpha\n$$\n```math\n\\sum_{i} x_i\n```\n$\\unknown{x}$\n";
+        let lines = |math| -> Result<Vec<String>> {
+            let parser = parse_with(md, ParseOptions::gfm().math(math))?;
+            Ok(parser.lines.iter().cloned().map(String::from).collect())
+        };
+        assert_eq!(
+            lines(true)?,
+            vec!["area πr² costs $5 `$x$`", "", "a/b ≤ α", "", "```math", "∑ᵢ xᵢ", "```", "\\unknown{x}"]
+        );
+        assert_eq!(lines(false)?[0], "area $\\pi r^2$ costs $5 `$x$`");
+        assert_eq!(lines(false)?[2], "\\frac{a}{b} \\le \\alpha");
+
+        // without math `$` is text, markup between two of them still counts
+        let parser = parse_with("pay $a [link](http://x) `b`$\n", ParseOptions::gfm())?;
+        assert_eq!(parser.links.len(), 1);
+        assert_eq!(parser.links[0].url, "http://x");
+        assert_eq!(parser.lines[0].spans.iter().filter(|s| s.style == parser.style.backtick).count(), 2);
+
+        let parser = parse_with(md, ParseOptions::gfm().math(true))?;
+        let pi = parser.lines[0].spans.iter().find(|s| s.content == "πr²");
+        assert_eq!(pi.map(|s| s.style), Some(parser.style.math));
 
-        assert_eq!(true, true);
+        let deep = format!("{}x{}", "{".repeat(200_000), "}".repeat(200_000));
+        let parser = parse_with(&format!("${}$", deep), ParseOptions::gfm().math(true))?;
+        assert_eq!(String::from(parser.lines[0].clone()), deep);
         Ok(())
     }
 }
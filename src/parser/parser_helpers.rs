@@ -6,7 +6,7 @@ use super::lexer::Token;
 
 
 pub fn generate_heading(heading: &usize, style: &MdStyle) -> Span<'static>{
-    Span::styled("#".repeat(heading.clone()), style.heading)
+    Span::styled("#".repeat(*heading), style.heading)
 }
 
 pub fn generate_indent(indent: String, style: &MdStyle) -> Span<'static> {
@@ -18,7 +18,7 @@ pub fn genarate_list_start(token: Token,  style: &MdStyle) -> Span<'static> {
         Token::Plus => Span::styled("+", style.list),
         Token::Dash => Span::styled("-", style.list),
         Token::Asterisk => Span::styled("*", style.list),
-        Token::Indent(i) => Span::styled(format!("{}", i) ,style.list),
+        Token::Indent(i) => Span::styled(i, style.list),
         _ => Span::from("")
     }
 }
@@ -31,3 +31,15 @@ pub fn generate_horizontal_rule(token: Token, style: &MdStyle) -> Span<'static>
         _ => Span::from(""),
     }
 }
+
+/// superscript form of `c`, if unicode has one
+pub fn superscript(c: char) -> Option<char> {
+    let i = "0123456789+-=()abcdefghijklmnoprstuvwxyz".find(c)?;
+    "⁰¹²³⁴⁵⁶⁷⁸⁹⁺⁻⁼⁽⁾ᵃᵇᶜᵈᵉᶠᵍʰⁱʲᵏˡᵐⁿᵒᵖʳˢᵗᵘᵛʷˣʸᶻ".chars().nth(i)
+}
+
+/// subscript form of `c`, if unicode has one
+pub fn subscript(c: char) -> Option<char> {
+    let i = "0123456789+-=()aehijklmnoprstuvx".find(c)?;
+    "₀₁₂₃₄₅₆₇₈₉₊₋₌₍₎ₐₑₕᵢⱼₖₗₘₙₒₚᵣₛₜᵤᵥₓ".chars().nth(i)
+}
@@ -0,0 +1,216 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use super::style::MdStyle;
+
+/// builds `MdStyle` from a base `text` style, every other style is patched on top of it.
+/// Patching follows ratatui `Style::patch`, colors of the patch replace base colors and
+/// modifiers are added (or removed with `remove_modifier`)
+///
+/// ```rust
+/// use md_to_tui::style::style::MdStyle;
+/// use ratatui::style::{Color, Style};
+///
+/// let style = MdStyle::builder()
+///     .text(Style::default().fg(Color::Black).bg(Color::White))
+///     .accent(Color::Blue)
+///     .build();
+/// assert_eq!(style.h1.bg, Some(Color::White));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct MdStyleBuilder {
+    base: Style,
+    patches: MdStyle,
+}
+
+macro_rules! setters {
+    ($($name:ident),* $(,)?) => {
+        $(
+            pub fn $name(mut self, style: Style) -> MdStyleBuilder {
+                self.patches.$name = style;
+                self
+            }
+        )*
+    };
+}
+
+impl Default for MdStyleBuilder {
+    fn default() -> Self {
+        MdStyleBuilder::from(MdStyle::default())
+    }
+}
+
+impl From<MdStyle> for MdStyleBuilder {
+    /// uses `text` of the style as the base and the rest as patches
+    fn from(style: MdStyle) -> Self {
+        MdStyleBuilder {
+            base: style.text,
+            patches: MdStyle {
+                text: Style::default(),
+                ..style
+            },
+        }
+    }
+}
+
+impl MdStyleBuilder {
+    pub fn new() -> MdStyleBuilder {
+        MdStyleBuilder::default()
+    }
+
+    /// base style every other style is patched onto
+    pub fn text(mut self, style: Style) -> MdStyleBuilder {
+        self.base = style;
+        self
+    }
+
+    setters!(
+        heading,
+        h1,
+        h2,
+        h3,
+        h4,
+        h5,
+        h6,
+        underline_heading,
+        list,
+        paragram,
+        bold,
+        italic,
+        backtick,
+        blocqoutes,
+        horizontal_rule,
+        colored,
+        link,
+        link_text,
+        focused_link,
+        tag,
+        table,
+        math,
+        note,
+        tip,
+        important,
+        warning,
+        caution,
+        search_match,
+        search_current,
+        fold,
+    );
+
+    /// uses `color` for headings, lists, links and highlights
+    pub fn accent(mut self, color: Color) -> MdStyleBuilder {
+        let fg = Style::default().fg(color);
+        let p = &mut self.patches;
+        p.heading = fg;
+        p.h1 = fg.add_modifier(Modifier::BOLD);
+        p.h2 = fg.add_modifier(Modifier::BOLD);
+        p.h3 = fg;
+        p.h4 = fg;
+        p.h5 = fg.add_modifier(Modifier::ITALIC);
+        p.h6 = fg.add_modifier(Modifier::ITALIC);
+        p.underline_heading = fg.add_modifier(Modifier::BOLD);
+        p.list = fg;
+        p.horizontal_rule = fg;
+        p.link = fg.add_modifier(Modifier::UNDERLINED);
+        p.link_text = fg;
+        p.focused_link = Style::default().fg(Color::Black).bg(color);
+        p.tag = Style::default().fg(Color::Black).bg(color);
+        p.search_current = Style::default().fg(Color::Black).bg(color);
+        self
+    }
+
+    pub fn build(self) -> MdStyle {
+        let base = self.base;
+        let style = self.patches.map_styles(|p| base.patch(p));
+        MdStyle { text: base, ..style }
+    }
+}
+
+impl MdStyle {
+    pub fn builder() -> MdStyleBuilder {
+        MdStyleBuilder::new()
+    }
+
+    /// same style with `color` used for headings, lists, links and highlights
+    pub fn with_accent(self, color: Color) -> MdStyle {
+        MdStyleBuilder::from(self).accent(color).build()
+    }
+
+    /// combines nested styles, from outermost to innermost, on top of `text`.
+    /// Every style only contributes what differs from `text`, so inner colors win
+    /// and modifiers add up.
+    /// For bold inside a link inside a heading use `cascade(&[style.h1, style.link, style.bold])`
+    pub fn cascade(&self, styles: &[Style]) -> Style {
+        self.text.patch(self.nested(styles))
+    }
+
+    /// what nested styles change on top of `text`, the patch `cascade` applies to it
+    pub(crate) fn nested(&self, styles: &[Style]) -> Style {
+        styles
+            .iter()
+            .fold(Style::default(), |acc, s| acc.patch(self.difference(*s)))
+    }
+
+    /// part of `style` that differs from `text`
+    fn difference(&self, style: Style) -> Style {
+        let text = self.text;
+        Style {
+            fg: style.fg.filter(|c| Some(*c) != text.fg),
+            bg: style.bg.filter(|c| Some(*c) != text.bg),
+            add_modifier: style.add_modifier - text.add_modifier,
+            sub_modifier: style.sub_modifier,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::style::{Color, Modifier, Style};
+
+    use crate::style::style::MdStyle;
+
+    #[test]
+    fn base_and_patches() {
+        let style = MdStyle::builder()
+            .text(Style::default().fg(Color::Black).bg(Color::White))
+            .h1(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+            .bold(Style::default().add_modifier(Modifier::BOLD))
+            .build();
+
+        assert_eq!(style.text, Style::default().fg(Color::Black).bg(Color::White));
+        assert_eq!(
+            style.h1,
+            Style::default().fg(Color::Red).bg(Color::White).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(style.bold.fg, Some(Color::Black));
+        assert_eq!(style.paragram.bg, Some(Color::White));
+    }
+
+    #[test]
+    fn cascading() {
+        let style = MdStyle::builder()
+            .text(Style::default().fg(Color::White))
+            .h1(Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))
+            .link(Style::default().fg(Color::Blue).add_modifier(Modifier::UNDERLINED))
+            .italic(Style::default().add_modifier(Modifier::ITALIC))
+            .build();
+
+        let nested = style.cascade(&[style.h1, style.link, style.italic]);
+        assert_eq!(nested.fg, Some(Color::Blue));
+        assert_eq!(
+            nested.add_modifier,
+            Modifier::BOLD | Modifier::UNDERLINED | Modifier::ITALIC
+        );
+        assert_eq!(style.cascade(&[]), style.text);
+        assert_eq!(style.cascade(&[style.link, style.h1]).fg, Some(Color::Magenta));
+    }
+
+    #[test]
+    fn accent() {
+        let style = MdStyle::default().with_accent(Color::Green);
+        assert_eq!(style.h1.fg, Some(Color::Green));
+        assert_eq!(style.link.fg, Some(Color::Green));
+        assert_eq!(style.focused_link.bg, Some(Color::Green));
+        assert_eq!(style.text, MdStyle::default().text);
+        assert_eq!(style.backtick, MdStyle::default().text.patch(MdStyle::default().backtick));
+    }
+}
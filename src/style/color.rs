@@ -0,0 +1,196 @@
+use std::env;
+
+use ratatui::style::{Color, Modifier, Style};
+
+use super::style::MdStyle;
+
+/// colors terminal can display
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ColorDepth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    /// no colors, only modifiers
+    Monochrome,
+}
+
+/// rgb values of the 16 ansi colors, in `Color::Indexed` order
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (128, 0, 0)),
+    (Color::Green, (0, 128, 0)),
+    (Color::Yellow, (128, 128, 0)),
+    (Color::Blue, (0, 0, 128)),
+    (Color::Magenta, (128, 0, 128)),
+    (Color::Cyan, (0, 128, 128)),
+    (Color::Gray, (192, 192, 192)),
+    (Color::DarkGray, (128, 128, 128)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (0, 0, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// detects depth from environment, `NO_COLOR` wins over `COLORTERM` and `TERM`
+    pub fn detect() -> ColorDepth {
+        ColorDepth::from_env(
+            env::var("NO_COLOR").ok().as_deref(),
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    fn from_env(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+        if no_color.is_some_and(|v| !v.is_empty()) {
+            return ColorDepth::Monochrome;
+        }
+        if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+            return ColorDepth::TrueColor;
+        }
+        match term {
+            Some("dumb") => ColorDepth::Monochrome,
+            Some(t) if t.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    /// closest color this depth can display, `None` for monochrome
+    pub fn convert(&self, color: Color) -> Option<Color> {
+        match (self, color) {
+            (_, Color::Reset) | (ColorDepth::TrueColor, _) => Some(color),
+            (ColorDepth::Monochrome, _) => None,
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Some(Color::Indexed(rgb_to_256(r, g, b))),
+            (ColorDepth::Ansi256, c) => Some(c),
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => Some(nearest_ansi16((r, g, b))),
+            (ColorDepth::Ansi16, Color::Indexed(i)) => Some(nearest_ansi16(indexed_to_rgb(i))),
+            (ColorDepth::Ansi16, c) => Some(c),
+        }
+    }
+
+    /// converts colors of the style, in monochrome styles with background become reversed
+    pub fn convert_style(&self, style: Style) -> Style {
+        if *self == ColorDepth::Monochrome {
+            let mut out = Style::default()
+                .add_modifier(style.add_modifier)
+                .remove_modifier(style.sub_modifier);
+            if style.bg.is_some_and(|c| c != Color::Reset) {
+                out = out.add_modifier(Modifier::REVERSED);
+            }
+            return out;
+        }
+        Style {
+            fg: style.fg.and_then(|c| self.convert(c)),
+            bg: style.bg.and_then(|c| self.convert(c)),
+            ..style
+        }
+    }
+}
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| {
+        CUBE.iter()
+            .enumerate()
+            .min_by_key(|(_, c)| (**c as i32 - v as i32).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or(0)
+    };
+    let (cr, cg, cb) = (level(r), level(g), level(b));
+    let cube = (CUBE[cr as usize], CUBE[cg as usize], CUBE[cb as usize]);
+
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = ((avg.saturating_sub(3)) / 10).min(23) as u8;
+    let gray_v = 8 + gray * 10;
+
+    if distance((r, g, b), (gray_v, gray_v, gray_v)) < distance((r, g, b), cube) {
+        232 + gray
+    } else {
+        16 + 36 * cr + 6 * cg + cb
+    }
+}
+
+fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI16[i as usize].1,
+        16..=231 => {
+            let i = i - 16;
+            (CUBE[(i / 36) as usize], CUBE[(i / 6 % 6) as usize], CUBE[(i % 6) as usize])
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, c)| distance(rgb, *c))
+        .map(|(c, _)| *c)
+        .unwrap_or(Color::Reset)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+impl MdStyle {
+    /// converts every style to colors `depth` can display
+    pub fn downgrade(&self, depth: ColorDepth) -> MdStyle {
+        self.map_styles(|s| depth.convert_style(s))
+    }
+
+    /// same as `downgrade` with `ColorDepth::detect()`
+    pub fn downgrade_auto(&self) -> MdStyle {
+        self.downgrade(ColorDepth::detect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::style::{Color, Modifier, Style};
+
+    use crate::style::style::MdStyle;
+
+    use super::ColorDepth;
+
+    #[test]
+    fn detect_from_env() {
+        assert_eq!(ColorDepth::from_env(Some("1"), Some("truecolor"), None), ColorDepth::Monochrome);
+        assert_eq!(ColorDepth::from_env(Some(""), Some("24bit"), None), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, None, Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(None, None, Some("dumb")), ColorDepth::Monochrome);
+    }
+
+    #[test]
+    fn convert_colors() {
+        let orange = Color::Rgb(0xff, 0x88, 0x00);
+        assert_eq!(ColorDepth::TrueColor.convert(orange), Some(orange));
+        assert_eq!(ColorDepth::Ansi256.convert(orange), Some(Color::Indexed(208)));
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Rgb(0x80, 0x80, 0x80)), Some(Color::Indexed(244)));
+        assert_eq!(ColorDepth::Ansi16.convert(orange), Some(Color::LightYellow));
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Indexed(196)), Some(Color::LightRed));
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Cyan), Some(Color::Cyan));
+        assert_eq!(ColorDepth::Monochrome.convert(Color::Cyan), None);
+    }
+
+    #[test]
+    fn downgrade_style() {
+        let style = MdStyle::solarized().downgrade(ColorDepth::Ansi16);
+        assert!(matches!(style.h1.fg, Some(c) if !matches!(c, Color::Rgb(..) | Color::Indexed(_))));
+        assert_eq!(style.h1.add_modifier, Modifier::BOLD);
+
+        let style = MdStyle::default().downgrade(ColorDepth::Monochrome);
+        assert_eq!(style.search_match, Style::default().add_modifier(Modifier::REVERSED));
+        assert_eq!(style.h1, Style::default());
+    }
+}
@@ -1 +1,8 @@
-pub mod  style;
+#[allow(clippy::module_inception)]
+pub mod style;
+pub mod builder;
+pub mod color;
+pub mod presets;
+pub mod symbols;
+#[cfg(feature = "serde")]
+pub mod theme;
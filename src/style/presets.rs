@@ -0,0 +1,228 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use super::style::MdStyle;
+
+/// names accepted by `MdStyle::preset`
+pub const PRESETS: [&str; 5] = ["dark", "light", "solarized", "high-contrast", "monochrome"];
+
+const BASE03: Color = Color::Rgb(0x00, 0x2b, 0x36);
+const BASE02: Color = Color::Rgb(0x07, 0x36, 0x42);
+const BASE01: Color = Color::Rgb(0x58, 0x6e, 0x75);
+const BASE0: Color = Color::Rgb(0x83, 0x94, 0x96);
+const BASE1: Color = Color::Rgb(0x93, 0xa1, 0xa1);
+const YELLOW: Color = Color::Rgb(0xb5, 0x89, 0x00);
+const ORANGE: Color = Color::Rgb(0xcb, 0x4b, 0x16);
+const RED: Color = Color::Rgb(0xdc, 0x32, 0x2f);
+const MAGENTA: Color = Color::Rgb(0xd3, 0x36, 0x82);
+const VIOLET: Color = Color::Rgb(0x6c, 0x71, 0xc4);
+const BLUE: Color = Color::Rgb(0x26, 0x8b, 0xd2);
+const CYAN: Color = Color::Rgb(0x2a, 0xa1, 0x98);
+const GREEN: Color = Color::Rgb(0x85, 0x99, 0x00);
+
+impl MdStyle {
+    /// preset by name, one of `PRESETS`
+    pub fn preset(name: &str) -> Option<MdStyle> {
+        match name.trim().to_lowercase().replace('_', "-").as_str() {
+            "dark" => Some(MdStyle::dark()),
+            "light" => Some(MdStyle::light()),
+            "solarized" => Some(MdStyle::solarized()),
+            "high-contrast" => Some(MdStyle::high_contrast()),
+            "monochrome" => Some(MdStyle::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// for dark terminals, same as `MdStyle::default()`
+    pub fn dark() -> MdStyle {
+        MdStyle::default()
+    }
+
+    /// for light terminals, dark text and no black backgrounds
+    pub fn light() -> MdStyle {
+        MdStyle {
+            heading: Style::default().fg(Color::Blue),
+            h1: Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            h2: Style::default().fg(Color::Magenta),
+            h3: Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            h4: Style::default().fg(Color::Blue),
+            h5: Style::default().fg(Color::Green),
+            h6: Style::default().fg(Color::Cyan),
+
+            underline_heading: Style::default().fg(Color::Green),
+            list: Style::default().fg(Color::Red),
+            paragram: Style::default().fg(Color::DarkGray),
+            text: Style::default().fg(Color::Black),
+
+            bold: Style::default().add_modifier(Modifier::BOLD),
+            italic: Style::default().add_modifier(Modifier::ITALIC),
+            backtick: Style::default().fg(Color::Red).bg(Color::Gray),
+            blocqoutes: Style::default().fg(Color::DarkGray).bg(Color::Gray),
+            horizontal_rule: Style::default().fg(Color::DarkGray),
+            colored: Style::default().bg(Color::LightYellow),
+
+            link: Style::default().fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+            link_text: Style::default().fg(Color::Magenta),
+            focused_link: Style::default().add_modifier(Modifier::REVERSED),
+            tag: Style::default().fg(Color::Black).bg(Color::LightCyan),
+            table: Style::default().fg(Color::DarkGray),
+            math: Style::default().fg(Color::Magenta),
+            note: Style::default().fg(Color::Blue),
+            tip: Style::default().fg(Color::Green),
+            important: Style::default().fg(Color::Magenta),
+            warning: Style::default().fg(Color::Yellow),
+            caution: Style::default().fg(Color::Red),
+
+            search_match: Style::default().fg(Color::Black).bg(Color::LightYellow),
+            search_current: Style::default().fg(Color::White).bg(Color::Red),
+            fold: Style::default().fg(Color::DarkGray),
+        }
+    }
+
+    /// solarized dark palette, needs truecolor terminal
+    pub fn solarized() -> MdStyle {
+        MdStyle {
+            heading: Style::default().fg(BLUE),
+            h1: Style::default().fg(ORANGE).add_modifier(Modifier::BOLD),
+            h2: Style::default().fg(YELLOW).add_modifier(Modifier::BOLD),
+            h3: Style::default().fg(GREEN),
+            h4: Style::default().fg(CYAN),
+            h5: Style::default().fg(BLUE),
+            h6: Style::default().fg(VIOLET),
+
+            underline_heading: Style::default().fg(GREEN),
+            list: Style::default().fg(MAGENTA),
+            paragram: Style::default().fg(BASE01),
+            text: Style::default().fg(BASE0),
+
+            bold: Style::default().fg(BASE1).add_modifier(Modifier::BOLD),
+            italic: Style::default().add_modifier(Modifier::ITALIC),
+            backtick: Style::default().fg(CYAN).bg(BASE02),
+            blocqoutes: Style::default().fg(BASE01).bg(BASE02),
+            horizontal_rule: Style::default().fg(BASE01),
+            colored: Style::default().fg(BASE03).bg(YELLOW),
+
+            link: Style::default().fg(BLUE).add_modifier(Modifier::UNDERLINED),
+            link_text: Style::default().fg(VIOLET),
+            focused_link: Style::default().fg(BASE03).bg(BLUE),
+            tag: Style::default().fg(BASE03).bg(CYAN),
+            table: Style::default().fg(BASE01),
+            math: Style::default().fg(VIOLET),
+            note: Style::default().fg(BLUE),
+            tip: Style::default().fg(GREEN),
+            important: Style::default().fg(VIOLET),
+            warning: Style::default().fg(YELLOW),
+            caution: Style::default().fg(RED),
+
+            search_match: Style::default().fg(BASE03).bg(YELLOW),
+            search_current: Style::default().fg(BASE03).bg(RED),
+            fold: Style::default().fg(BASE01),
+        }
+    }
+
+    /// only black, white and bright colors with bold headings
+    pub fn high_contrast() -> MdStyle {
+        let bold = Modifier::BOLD;
+        MdStyle {
+            heading: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            h1: Style::default().fg(Color::LightYellow).add_modifier(bold | Modifier::UNDERLINED),
+            h2: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            h3: Style::default().fg(Color::LightCyan).add_modifier(bold),
+            h4: Style::default().fg(Color::LightCyan),
+            h5: Style::default().fg(Color::LightGreen).add_modifier(bold),
+            h6: Style::default().fg(Color::LightGreen),
+
+            underline_heading: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            list: Style::default().fg(Color::LightYellow),
+            paragram: Style::default().fg(Color::White),
+            text: Style::default().fg(Color::White),
+
+            bold: Style::default().add_modifier(bold),
+            italic: Style::default().add_modifier(Modifier::ITALIC),
+            backtick: Style::default().fg(Color::Black).bg(Color::White),
+            blocqoutes: Style::default().fg(Color::White).add_modifier(Modifier::ITALIC),
+            horizontal_rule: Style::default().fg(Color::White).add_modifier(bold),
+            colored: Style::default().fg(Color::Black).bg(Color::LightYellow),
+
+            link: Style::default().fg(Color::LightCyan).add_modifier(Modifier::UNDERLINED),
+            link_text: Style::default().fg(Color::LightCyan).add_modifier(bold),
+            focused_link: Style::default().fg(Color::Black).bg(Color::LightCyan),
+            tag: Style::default().fg(Color::Black).bg(Color::White),
+            table: Style::default().fg(Color::White),
+            math: Style::default().fg(Color::LightYellow),
+            note: Style::default().fg(Color::LightCyan).add_modifier(bold),
+            tip: Style::default().fg(Color::LightGreen).add_modifier(bold),
+            important: Style::default().fg(Color::LightMagenta).add_modifier(bold),
+            warning: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            caution: Style::default().fg(Color::LightRed).add_modifier(bold),
+
+            search_match: Style::default().fg(Color::Black).bg(Color::LightYellow),
+            search_current: Style::default().fg(Color::Black).bg(Color::LightMagenta),
+            fold: Style::default().fg(Color::White).add_modifier(bold),
+        }
+    }
+
+    /// no colors, only modifiers
+    pub fn monochrome() -> MdStyle {
+        let plain = Style::default();
+        MdStyle {
+            heading: plain.add_modifier(Modifier::BOLD),
+            h1: plain.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            h2: plain.add_modifier(Modifier::BOLD),
+            h3: plain.add_modifier(Modifier::BOLD),
+            h4: plain.add_modifier(Modifier::BOLD),
+            h5: plain.add_modifier(Modifier::BOLD | Modifier::DIM),
+            h6: plain.add_modifier(Modifier::BOLD | Modifier::DIM),
+
+            underline_heading: plain.add_modifier(Modifier::BOLD),
+            list: plain.add_modifier(Modifier::BOLD),
+            paragram: plain,
+            text: plain,
+
+            bold: plain.add_modifier(Modifier::BOLD),
+            italic: plain.add_modifier(Modifier::ITALIC),
+            backtick: plain.add_modifier(Modifier::DIM),
+            blocqoutes: plain.add_modifier(Modifier::ITALIC),
+            horizontal_rule: plain.add_modifier(Modifier::DIM),
+            colored: plain.add_modifier(Modifier::REVERSED),
+
+            link: plain.add_modifier(Modifier::UNDERLINED),
+            link_text: plain.add_modifier(Modifier::UNDERLINED),
+            focused_link: plain.add_modifier(Modifier::REVERSED),
+            tag: plain.add_modifier(Modifier::REVERSED),
+            table: plain.add_modifier(Modifier::DIM),
+            math: plain.add_modifier(Modifier::ITALIC),
+            note: plain,
+            tip: plain,
+            important: plain.add_modifier(Modifier::BOLD),
+            warning: plain.add_modifier(Modifier::BOLD),
+            caution: plain.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+
+            search_match: plain.add_modifier(Modifier::REVERSED),
+            search_current: plain.add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            fold: plain.add_modifier(Modifier::DIM),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::style::style::MdStyle;
+
+    use super::PRESETS;
+
+    #[test]
+    fn presets_by_name() {
+        for name in PRESETS {
+            assert!(MdStyle::preset(name).is_some(), "{}", name);
+        }
+        assert_eq!(MdStyle::preset("High_Contrast"), Some(MdStyle::high_contrast()));
+        assert_eq!(MdStyle::preset("neon"), None);
+    }
+
+    #[test]
+    fn preset_snapshots() {
+        for name in PRESETS {
+            insta::assert_debug_snapshot!(name, MdStyle::preset(name).unwrap());
+        }
+    }
+}
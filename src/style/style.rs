@@ -1,8 +1,15 @@
 use ratatui::style::{Style, Color, Modifier};
 
+pub use crate::parser::admonition::AdmonitionKind;
+
 
 #[allow(dead_code)]
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::style::theme::Theme", into = "crate::style::theme::Theme")
+)]
 pub struct MdStyle {
   pub heading: Style,
   pub h1: Style,
@@ -27,7 +34,20 @@ pub struct MdStyle {
 
   pub link: Style,
   pub link_text: Style,
+  pub focused_link: Style,
   pub tag: Style,
+  pub table: Style,
+  pub math: Style,
+
+  pub note: Style,
+  pub tip: Style,
+  pub important: Style,
+  pub warning: Style,
+  pub caution: Style,
+
+  pub search_match: Style,
+  pub search_current: Style,
+  pub fold: Style,
 }
 
 impl Default for MdStyle {
@@ -55,7 +75,69 @@ impl Default for MdStyle {
 
         link: Style::default().fg(Color::Blue),
         link_text:  Style::default().fg(Color::Red),
-        tag: Style::default().bg(Color::Cyan)
+        focused_link: Style::default().add_modifier(Modifier::REVERSED),
+        tag: Style::default().bg(Color::Cyan),
+        table: Style::default().fg(Color::Gray),
+        math: Style::default().fg(Color::LightMagenta),
+
+        note: Style::default().fg(Color::LightBlue),
+        tip: Style::default().fg(Color::LightGreen),
+        important: Style::default().fg(Color::LightMagenta),
+        warning: Style::default().fg(Color::Yellow),
+        caution: Style::default().fg(Color::LightRed),
+
+        search_match: Style::default().fg(Color::Black).bg(Color::Yellow),
+        search_current: Style::default().fg(Color::Black).bg(Color::LightRed),
+        fold: Style::default().fg(Color::DarkGray),
+        }
+    }
+}
+
+impl MdStyle {
+    /// applies `f` to every style
+    pub fn map_styles<F: Fn(Style) -> Style>(&self, f: F) -> MdStyle {
+        MdStyle {
+            heading: f(self.heading),
+            h1: f(self.h1),
+            h2: f(self.h2),
+            h3: f(self.h3),
+            h4: f(self.h4),
+            h5: f(self.h5),
+            h6: f(self.h6),
+            underline_heading: f(self.underline_heading),
+            list: f(self.list),
+            text: f(self.text),
+            paragram: f(self.paragram),
+            bold: f(self.bold),
+            italic: f(self.italic),
+            backtick: f(self.backtick),
+            blocqoutes: f(self.blocqoutes),
+            horizontal_rule: f(self.horizontal_rule),
+            colored: f(self.colored),
+            link: f(self.link),
+            link_text: f(self.link_text),
+            focused_link: f(self.focused_link),
+            tag: f(self.tag),
+            table: f(self.table),
+            math: f(self.math),
+            note: f(self.note),
+            tip: f(self.tip),
+            important: f(self.important),
+            warning: f(self.warning),
+            caution: f(self.caution),
+            search_match: f(self.search_match),
+            search_current: f(self.search_current),
+            fold: f(self.fold),
+        }
+    }
+
+    pub fn admonition(&self, kind: AdmonitionKind) -> Style {
+        match kind {
+            AdmonitionKind::Note => self.note,
+            AdmonitionKind::Tip => self.tip,
+            AdmonitionKind::Important => self.important,
+            AdmonitionKind::Warning => self.warning,
+            AdmonitionKind::Caution => self.caution,
         }
     }
 }
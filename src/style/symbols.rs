@@ -0,0 +1,153 @@
+use super::style::AdmonitionKind;
+
+/// characters drawn by the parser in place of markdown syntax,
+/// `MdSymbols::default()` keeps the markdown as it was written
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MdSymbols {
+    /// list bullets by nesting depth, repeats when the list is deeper,
+    /// empty keeps `-`, `*` or `+` from the text
+    pub bullets: Vec<String>,
+    pub checked: String,
+    pub unchecked: String,
+    /// drawn for every `>` at the start of the line
+    pub quote: String,
+    /// repeated `rule_width` times for `---`, `***` and `___`, empty keeps the text
+    pub rule: String,
+    pub rule_width: usize,
+    /// drawn in place of `#` for every heading level
+    pub headings: [String; 6],
+    /// opening fence, followed by language of the code block
+    pub code_top: String,
+    pub code_bottom: String,
+    /// drawn before every line of a code block
+    pub code_side: String,
+    /// drawn before every link
+    pub link_marker: String,
+    /// drawn before text of html `<summary>`
+    pub summary: String,
+    /// drawn by `MdView` next to headings of folded and unfolded sections
+    pub folded: String,
+    pub unfolded: String,
+    /// drawn by `MdView` before the count of lines a folded section hides
+    pub fold_summary: String,
+    /// drawn before the title of admonitions of each kind
+    pub note: String,
+    pub tip: String,
+    pub important: String,
+    pub warning: String,
+    pub caution: String,
+}
+
+impl Default for MdSymbols {
+    fn default() -> Self {
+        MdSymbols {
+            bullets: vec![],
+            checked: "[x]".into(),
+            unchecked: "[ ]".into(),
+            quote: ">".into(),
+            rule: "".into(),
+            rule_width: 0,
+            headings: ["#", "##", "###", "####", "#####", "######"].map(String::from),
+            code_top: "```".into(),
+            code_bottom: "```".into(),
+            code_side: "".into(),
+            link_marker: "".into(),
+            summary: "".into(),
+            folded: "+".into(),
+            unfolded: "-".into(),
+            fold_summary: "... ".into(),
+            note: "".into(),
+            tip: "".into(),
+            important: "".into(),
+            warning: "".into(),
+            caution: "".into(),
+        }
+    }
+}
+
+impl MdSymbols {
+    /// markdown as it was written, same as `MdSymbols::default()`
+    pub fn raw() -> MdSymbols {
+        MdSymbols::default()
+    }
+
+    /// only ascii characters, for terminals with poor font coverage
+    pub fn ascii() -> MdSymbols {
+        MdSymbols {
+            bullets: ["*", "-", "+"].map(String::from).to_vec(),
+            checked: "[x]".into(),
+            unchecked: "[ ]".into(),
+            quote: "|".into(),
+            rule: "-".into(),
+            rule_width: 40,
+            headings: ["#", "##", "###", "####", "#####", "######"].map(String::from),
+            code_top: "+-- ".into(),
+            code_bottom: "+--".into(),
+            code_side: "| ".into(),
+            link_marker: "".into(),
+            summary: "> ".into(),
+            folded: "+".into(),
+            unfolded: "-".into(),
+            fold_summary: "... ".into(),
+            note: "(i) ".into(),
+            tip: "(*) ".into(),
+            important: "(!) ".into(),
+            warning: "/!\\ ".into(),
+            caution: "(x) ".into(),
+        }
+    }
+
+    pub fn unicode() -> MdSymbols {
+        MdSymbols {
+            bullets: ["•", "◦", "▪", "▫"].map(String::from).to_vec(),
+            checked: "☑".into(),
+            unchecked: "☐".into(),
+            quote: "│".into(),
+            rule: "─".into(),
+            rule_width: 40,
+            headings: ["◉", "◎", "●", "○", "◆", "◇"].map(String::from),
+            code_top: "╭── ".into(),
+            code_bottom: "╰──".into(),
+            code_side: "│ ".into(),
+            link_marker: "↗".into(),
+            summary: "▸ ".into(),
+            folded: "▸".into(),
+            unfolded: "▾".into(),
+            fold_summary: "⋯ ".into(),
+            note: "ⓘ ".into(),
+            tip: "✦ ".into(),
+            important: "‼ ".into(),
+            warning: "⚠ ".into(),
+            caution: "✖ ".into(),
+        }
+    }
+
+    /// bullet for list at `depth`, `None` if bullet from the text should be kept
+    pub fn bullet(&self, depth: usize) -> Option<&str> {
+        if self.bullets.is_empty() {
+            return None;
+        }
+        Some(&self.bullets[depth % self.bullets.len()])
+    }
+
+    pub fn heading(&self, level: usize) -> &str {
+        &self.headings[level.clamp(1, 6) - 1]
+    }
+
+    /// icon drawn before the title of `kind` admonitions
+    ///
+    /// ```rust
+    /// use md_to_tui::style::{style::AdmonitionKind, symbols::MdSymbols};
+    ///
+    /// assert_eq!(MdSymbols::ascii().admonition(AdmonitionKind::Warning), "/!\\ ");
+    /// ```
+    pub fn admonition(&self, kind: AdmonitionKind) -> &str {
+        match kind {
+            AdmonitionKind::Note => &self.note,
+            AdmonitionKind::Tip => &self.tip,
+            AdmonitionKind::Important => &self.important,
+            AdmonitionKind::Warning => &self.warning,
+            AdmonitionKind::Caution => &self.caution,
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    document::document::SourceSpan,
+    error::{Error, ErrorKind, ThemeError},
+    style::style::MdStyle,
+};
+
+const COLORS: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("gray", Color::Gray),
+    ("dark-gray", Color::DarkGray),
+    ("light-red", Color::LightRed),
+    ("light-green", Color::LightGreen),
+    ("light-yellow", Color::LightYellow),
+    ("light-blue", Color::LightBlue),
+    ("light-magenta", Color::LightMagenta),
+    ("light-cyan", Color::LightCyan),
+    ("white", Color::White),
+];
+
+const MODIFIERS: [(&str, Modifier); 9] = [
+    ("bold", Modifier::BOLD),
+    ("dim", Modifier::DIM),
+    ("italic", Modifier::ITALIC),
+    ("underlined", Modifier::UNDERLINED),
+    ("slow-blink", Modifier::SLOW_BLINK),
+    ("rapid-blink", Modifier::RAPID_BLINK),
+    ("reversed", Modifier::REVERSED),
+    ("hidden", Modifier::HIDDEN),
+    ("crossed-out", Modifier::CROSSED_OUT),
+];
+
+/// `dark-gray`, `DarkGray`, `dark_gray` and `DARK_GRAY` are all the same name
+fn normalize(name: &str) -> String {
+    let mut out = String::new();
+    let mut lower = false;
+    for c in name.trim().chars() {
+        if c.is_uppercase() && lower {
+            out.push('-');
+        }
+        lower = c.is_lowercase();
+        out.push(if c == '_' || c == ' ' { '-' } else { c.to_ascii_lowercase() });
+    }
+    out.replace("grey", "gray")
+}
+
+/// parses color name (`light-red`), hex (`#ff8800`) or 256 color index (`208`)
+pub fn parse_color(s: &str) -> Option<Color> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let c = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Color::Rgb((c >> 16) as u8, (c >> 8) as u8, c as u8));
+    }
+    if let Ok(i) = s.parse::<u8>() {
+        return Some(Color::Indexed(i));
+    }
+    let name = normalize(s);
+    COLORS.iter().find(|(n, _)| *n == name).map(|(_, c)| *c)
+}
+
+pub fn color_name(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::Indexed(i) => i.to_string(),
+        c => COLORS
+            .iter()
+            .find(|(_, n)| *n == c)
+            .map(|(n, _)| n.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+pub fn parse_modifier(s: &str) -> Option<Modifier> {
+    let name = normalize(s);
+    MODIFIERS.iter().find(|(n, _)| *n == name).map(|(_, m)| *m)
+}
+
+fn modifier_name(modifier: Modifier) -> String {
+    MODIFIERS
+        .iter()
+        .find(|(_, m)| *m == modifier)
+        .map(|(n, _)| n.to_string())
+        .unwrap_or_default()
+}
+
+/// `{ fg = "#ff8800", bg = "black", modifiers = ["bold"], remove_modifiers = ["dim"] }`,
+/// names are checked when it becomes a `Style`
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bg: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    modifiers: Vec<String>,
+    /// `Style::sub_modifier`, taken off the style it is patched onto
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    remove_modifiers: Vec<String>,
+}
+
+impl From<Style> for StyleDef {
+    fn from(style: Style) -> Self {
+        let names = |modifier: Modifier| {
+            MODIFIERS
+                .iter()
+                .filter(|(_, m)| modifier.contains(*m))
+                .map(|(_, m)| modifier_name(*m))
+                .collect()
+        };
+        StyleDef {
+            fg: style.fg.map(color_name),
+            bg: style.bg.map(color_name),
+            modifiers: names(style.add_modifier),
+            remove_modifiers: names(style.sub_modifier),
+        }
+    }
+}
+
+impl TryFrom<StyleDef> for Style {
+    type Error = ThemeError;
+
+    fn try_from(def: StyleDef) -> Result<Self, Self::Error> {
+        let color = |name: String| parse_color(&name).ok_or(ThemeError::InvalidColor(name));
+        let mut style = Style::default();
+        if let Some(fg) = def.fg {
+            style = style.fg(color(fg)?);
+        }
+        if let Some(bg) = def.bg {
+            style = style.bg(color(bg)?);
+        }
+        for name in def.modifiers {
+            let modifier = parse_modifier(&name).ok_or(ThemeError::InvalidModifier(name))?;
+            style = style.add_modifier(modifier);
+        }
+        for name in def.remove_modifiers {
+            let modifier = parse_modifier(&name).ok_or(ThemeError::InvalidModifier(name))?;
+            style = style.remove_modifier(modifier);
+        }
+        Ok(style)
+    }
+}
+
+/// every style of `MdStyle` with its field name, the destructuring doesn't compile
+/// when a field is missing from the list
+macro_rules! named_styles {
+    ($style:expr) => {
+        named_styles!(
+            $style;
+            heading, h1, h2, h3, h4, h5, h6, underline_heading, list, text, paragram, bold, italic,
+            backtick, blocqoutes, horizontal_rule, colored, link, link_text, focused_link, tag, table,
+            math, note, tip, important, warning, caution, search_match, search_current, fold,
+        )
+    };
+    ($style:expr; $($name:ident),* $(,)?) => {{
+        let MdStyle { $($name),* } = $style;
+        [$((stringify!($name), $name)),*]
+    }};
+}
+
+/// `MdStyle` as written in theme files, table of `StyleDef` by field name in field order
+pub(crate) struct Theme(Vec<(String, StyleDef)>);
+
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(name, def)| (name, def)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let styles = BTreeMap::<String, StyleDef>::deserialize(deserializer)?;
+        Ok(Theme(styles.into_iter().collect()))
+    }
+}
+
+impl From<MdStyle> for Theme {
+    fn from(style: MdStyle) -> Self {
+        let styles = named_styles!(style);
+        Theme(styles.into_iter().map(|(name, s)| (name.to_string(), s.into())).collect())
+    }
+}
+
+/// missing styles are taken from `MdStyle::default()`
+impl TryFrom<Theme> for MdStyle {
+    type Error = ThemeError;
+
+    fn try_from(theme: Theme) -> Result<Self, Self::Error> {
+        let mut style = MdStyle::default();
+        let mut styles = named_styles!(&mut style);
+        for (name, def) in theme.0 {
+            let Some((_, field)) = styles.iter_mut().find(|(n, _)| *n == name) else {
+                return Err(ThemeError::UnknownStyle(name));
+            };
+            **field = def.try_into()?;
+        }
+        Ok(style)
+    }
+}
+
+impl MdStyle {
+    /// loads theme from toml, missing fields are taken from `MdStyle::default()`
+    pub fn from_toml(s: &str) -> Result<MdStyle, Error> {
+        let theme: Theme = toml::from_str(s).map_err(|e| {
+            let span = e.span().map(|r| span_of(s, r.start, r.end));
+            theme_error(ThemeError::Syntax(e.message().to_string()), span)
+        })?;
+        theme.try_into().map_err(|e| theme_error(e, None))
+    }
+
+    /// loads theme from json, missing fields are taken from `MdStyle::default()`
+    pub fn from_json(s: &str) -> Result<MdStyle, Error> {
+        let theme: Theme = serde_json::from_str(s).map_err(|e| {
+            // serde_json counts lines and columns from 1, 0 when they are unknown
+            let offset = s
+                .split_inclusive('\n')
+                .take(e.line().saturating_sub(1))
+                .map(str::len)
+                .sum::<usize>()
+                + e.column().saturating_sub(1);
+            let span = (e.line() > 0).then(|| span_of(s, offset, offset));
+            theme_error(ThemeError::Syntax(e.to_string()), span)
+        })?;
+        theme.try_into().map_err(|e| theme_error(e, None))
+    }
+
+    /// loads theme from `.toml` or `.json` file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<MdStyle, Error> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        if !matches!(extension, "toml" | "json") {
+            return Err(theme_error(ThemeError::UnknownFormat(extension.to_string()), None));
+        }
+        let text = fs::read_to_string(path)?;
+        match extension {
+            "toml" => MdStyle::from_toml(&text),
+            _ => MdStyle::from_json(&text),
+        }
+    }
+
+    pub fn to_toml(&self) -> Result<String, Error> {
+        toml::to_string(self).map_err(|_| theme_error(ThemeError::Serialize, None))
+    }
+}
+
+fn theme_error(e: ThemeError, span: Option<SourceSpan>) -> Error {
+    Error {
+        kind: ErrorKind::Theme(e),
+        span,
+    }
+}
+
+/// location of bytes `start..end` of `text`
+fn span_of(text: &str, start: usize, end: usize) -> SourceSpan {
+    let mut start = start.min(text.len());
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let before = &text[..start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    SourceSpan {
+        start,
+        end: end.clamp(start, text.len()),
+        line: before.matches('\n').count(),
+        column: before[line_start..].chars().count(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::style::{Color, Modifier, Style};
+
+    use crate::{
+        error::{Error, ErrorKind, ThemeError},
+        style::style::MdStyle,
+    };
+
+    use super::parse_color;
+
+    #[test]
+    fn partial_theme() {
+        let theme = r##"
+heading = { fg = "#ff8800", modifiers = ["bold"] }
+link = { fg = "LightBlue", bg = "236", modifiers = ["underlined", "italic"] }
+"##;
+        let style = MdStyle::from_toml(theme).unwrap();
+
+        assert_eq!(
+            style.heading,
+            Style::default().fg(Color::Rgb(0xff, 0x88, 0)).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            style.link,
+            Style::default()
+                .fg(Color::LightBlue)
+                .bg(Color::Indexed(236))
+                .add_modifier(Modifier::UNDERLINED | Modifier::ITALIC)
+        );
+        assert_eq!(style.h1, MdStyle::default().h1);
+
+        let json = r#"{ "text": { "fg": "dark_grey" } }"#;
+        assert_eq!(MdStyle::from_json(json).unwrap().text, Style::default().fg(Color::DarkGray));
+        for name in ["dark-gray", "DarkGray", "dark_gray", "DARK_GRAY", "darkGrey"] {
+            assert_eq!(parse_color(name), Some(Color::DarkGray), "{}", name);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let style = MdStyle::default();
+        let toml = style.to_toml().unwrap();
+        assert_eq!(MdStyle::from_toml(&toml).unwrap(), style);
+        // every field, in the order `MdStyle` declares them
+        assert!(toml.starts_with("[heading]\n"), "{}", toml);
+        assert_eq!(toml.matches("\n[").count() + 1, 31);
+        assert!(toml.trim_end().ends_with("[fold]\nfg = \"dark-gray\""), "{}", toml);
+
+        let style = MdStyle {
+            link: Style::default()
+                .add_modifier(Modifier::BOLD)
+                .remove_modifier(Modifier::DIM | Modifier::ITALIC),
+            ..MdStyle::default()
+        };
+        let toml = style.to_toml().unwrap();
+        assert!(toml.contains("remove_modifiers = [\"dim\", \"italic\"]"), "{}", toml);
+        assert_eq!(MdStyle::from_toml(&toml).unwrap(), style);
+    }
+
+    #[test]
+    fn descriptive_errors() {
+        let kind = |res: Result<MdStyle, Error>| res.unwrap_err().kind;
+        assert_eq!(
+            kind(MdStyle::from_toml("h1 = { fg = \"#ff88\" }")),
+            ErrorKind::Theme(ThemeError::InvalidColor("#ff88".into()))
+        );
+        assert_eq!(
+            kind(MdStyle::from_toml("h7 = { fg = \"red\" }")),
+            ErrorKind::Theme(ThemeError::UnknownStyle("h7".into()))
+        );
+        assert_eq!(
+            kind(MdStyle::from_json(r#"{ "h1": { "modifiers": ["loud"] } }"#)),
+            ErrorKind::Theme(ThemeError::InvalidModifier("loud".into()))
+        );
+
+        let md = "h1 = { fg = \"red\" }\nlink = { fg = }\n";
+        let err = MdStyle::from_toml(md).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Theme(ThemeError::Syntax(_))));
+        assert_eq!(err.span.map(|s| s.to_string()), Some("2:15".into()));
+        let err = MdStyle::from_json("{\n  \"h1\": 5 }").unwrap_err();
+        assert_eq!(err.span.map(|s| s.to_string()), Some("2:9".into()));
+
+        assert_eq!(
+            kind(MdStyle::from_path("theme.yaml")),
+            ErrorKind::Theme(ThemeError::UnknownFormat("yaml".into()))
+        );
+        assert_eq!(
+            kind(MdStyle::from_path("does/not/exist.toml")),
+            ErrorKind::Io(std::io::ErrorKind::NotFound)
+        );
+        assert_eq!(parse_color("nope"), None);
+    }
+}
@@ -0,0 +1,674 @@
+use std::collections::BTreeSet;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    widgets::{Block, StatefulWidget, Widget},
+};
+
+use crate::document::document::{MdDocument, MdHeading, MdLink, MdMatch};
+
+/// row on the screen, either line of the document or summary of a folded section
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Row {
+    Line(usize),
+    Summary(usize),
+}
+
+/// link that was drawn on the screen
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct LinkArea {
+    pub url: String,
+    pub target: Option<usize>,
+    pub area: Rect,
+}
+
+/// state of `MdView`, keeps scroll offset, focused link, search matches, folded sections
+/// and screen areas of links from the last render.
+/// `scroll` counts rows on the screen so folded sections are skipped, like the scroll of
+/// ratatui's `Paragraph` it is a `u16`, rows past 65535 can't be scrolled to
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MdViewState {
+    pub scroll: u16,
+    focused: Option<usize>,
+    links: Vec<MdLink>,
+    areas: Vec<LinkArea>,
+    matches: Vec<MdMatch>,
+    current_match: Option<usize>,
+    headings: Vec<MdHeading>,
+    line_count: usize,
+    folded: BTreeSet<usize>,
+    /// rows of the lines with `folded`, rebuilt when sections are folded or the document changes
+    rows: Vec<Row>,
+    /// row of every line, `None` for lines hidden in folded sections
+    row_of: Vec<Option<usize>>,
+    area: Rect,
+}
+
+impl MdViewState {
+    pub fn new() -> MdViewState {
+        MdViewState::default()
+    }
+
+    /// link areas recorded during the last render
+    pub fn link_areas(&self) -> &[LinkArea] {
+        &self.areas
+    }
+
+    /// returns url of link drawn at `(x, y)`
+    pub fn link_at(&self, x: u16, y: u16) -> Option<&str> {
+        self.area_at(x, y).map(|l| l.url.as_str())
+    }
+
+    fn area_at(&self, x: u16, y: u16) -> Option<&LinkArea> {
+        self.areas.iter().find(|l| {
+            x >= l.area.x
+                && x < l.area.x + l.area.width
+                && y >= l.area.y
+                && y < l.area.y + l.area.height
+        })
+    }
+
+    /// scrolls so that `line` is at the top of the view, unfolds sections hiding it,
+    /// lines past row 65535 stop at that row
+    pub fn scroll_to(&mut self, line: usize) {
+        self.reveal(line);
+        let row = self.row_of(line).unwrap_or(line);
+        self.scroll = row.min(u16::MAX as usize) as u16;
+    }
+
+    /// returns url of focused link, `#anchor` links scroll to their heading instead
+    pub fn activate(&mut self) -> Option<String> {
+        let link = self.focused_link()?.clone();
+        match link.target {
+            Some(line) => {
+                self.scroll_to(line);
+                None
+            }
+            None => Some(link.url),
+        }
+    }
+
+    /// same as `activate` for link drawn at `(x, y)`
+    pub fn activate_at(&mut self, x: u16, y: u16) -> Option<String> {
+        let link = self.area_at(x, y)?.clone();
+        match link.target {
+            Some(line) => {
+                self.scroll_to(line);
+                None
+            }
+            None => Some(link.url),
+        }
+    }
+
+    pub fn focused_link(&self) -> Option<&MdLink> {
+        self.links.get(self.focused?)
+    }
+
+    /// moves focus to next link, wraps around after the last one
+    pub fn focus_next(&mut self) {
+        if self.links.is_empty() {
+            return;
+        }
+        self.focused = Some(match self.focused {
+            Some(i) => (i + 1) % self.links.len(),
+            None => 0,
+        });
+        self.scroll_to_focused();
+    }
+
+    /// moves focus to previous link, wraps around before the first one
+    pub fn focus_prev(&mut self) {
+        if self.links.is_empty() {
+            return;
+        }
+        self.focused = Some(match self.focused {
+            Some(0) | None => self.links.len() - 1,
+            Some(i) => i - 1,
+        });
+        self.scroll_to_focused();
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focused = None;
+    }
+
+    fn scroll_to_focused(&mut self) {
+        if let Some(line) = self.focused_link().map(|l| l.line) {
+            self.scroll_into_view(line);
+        }
+    }
+
+    fn scroll_into_view(&mut self, line: usize) {
+        self.reveal(line);
+        let row = self.row_of(line).unwrap_or(line).min(u16::MAX as usize) as u16;
+        let height = self.area.height;
+        if row < self.scroll {
+            self.scroll = row;
+        } else if height > 0 && row >= self.scroll.saturating_add(height) {
+            self.scroll = row - height + 1;
+        }
+    }
+
+    /// line after the last line of section started by heading `i`
+    fn section_end(&self, i: usize) -> usize {
+        let level = self.headings[i].level;
+        self.headings[i + 1..]
+            .iter()
+            .find(|h| h.level <= level)
+            .map(|h| h.line)
+            .unwrap_or(self.line_count)
+    }
+
+    /// rebuilds `rows` and `row_of` from `folded`
+    fn layout(&mut self) {
+        self.rows.clear();
+        self.row_of = vec![None; self.line_count];
+        let mut line = 0;
+        while line < self.line_count {
+            self.row_of[line] = Some(self.rows.len());
+            self.rows.push(Row::Line(line));
+            if self.folded.contains(&line) {
+                if let Some(i) = self.headings.iter().position(|h| h.line == line) {
+                    let end = self.section_end(i);
+                    if end > line + 1 {
+                        self.rows.push(Row::Summary(end - line - 1));
+                    }
+                    line = end.max(line + 1);
+                    continue;
+                }
+            }
+            line += 1;
+        }
+    }
+
+    fn row_of(&self, line: usize) -> Option<usize> {
+        self.row_of.get(line).copied().flatten()
+    }
+
+    /// unfolds every section that hides `line`
+    fn reveal(&mut self, line: usize) {
+        let mut changed = false;
+        for i in 0..self.headings.len() {
+            let start = self.headings[i].line;
+            if start < line && line < self.section_end(i) {
+                changed |= self.folded.remove(&start);
+            }
+        }
+        if changed {
+            self.layout();
+        }
+    }
+
+    /// folds section started by heading at `line`
+    pub fn fold(&mut self, line: usize) {
+        if self.headings.iter().any(|h| h.line == line) && self.folded.insert(line) {
+            self.layout();
+        }
+    }
+
+    pub fn unfold(&mut self, line: usize) {
+        if self.folded.remove(&line) {
+            self.layout();
+        }
+    }
+
+    pub fn toggle_fold(&mut self, line: usize) {
+        if self.folded.contains(&line) {
+            self.unfold(line);
+        } else {
+            self.fold(line);
+        }
+    }
+
+    /// toggles section of the heading drawn at screen row `y`
+    pub fn toggle_fold_at(&mut self, y: u16) {
+        let row = y.checked_sub(self.area.y).map(|r| r as usize + self.scroll as usize);
+        if let Some(Row::Line(line)) = row.and_then(|r| self.rows.get(r).copied()) {
+            self.toggle_fold(line);
+        }
+    }
+
+    pub fn fold_all(&mut self) {
+        self.folded = self.headings.iter().map(|h| h.line).collect();
+        self.layout();
+    }
+
+    pub fn unfold_all(&mut self) {
+        self.folded.clear();
+        self.layout();
+    }
+
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.folded.contains(&line)
+    }
+
+    /// sets search results to highlight, current match becomes the first one
+    /// at or below the scroll offset
+    pub fn set_matches(&mut self, matches: Vec<MdMatch>) {
+        let rows = &self.rows;
+        let scroll = self.scroll as usize;
+        let top = match rows.get(scroll) {
+            Some(Row::Line(line)) => *line,
+            // lines hidden by a fold start after its heading
+            Some(Row::Summary(_)) => rows[..scroll]
+                .iter()
+                .rev()
+                .find_map(|r| match r {
+                    Row::Line(line) => Some(line + 1),
+                    Row::Summary(_) => None,
+                })
+                .unwrap_or(0),
+            None => self.line_count,
+        };
+        self.current_match = matches
+            .iter()
+            .position(|m| m.line >= top)
+            .or(if matches.is_empty() { None } else { Some(0) });
+        self.matches = matches;
+        if let Some(m) = self.current_match() {
+            self.scroll_into_view(m.line);
+        }
+    }
+
+    pub fn matches(&self) -> &[MdMatch] {
+        &self.matches
+    }
+
+    pub fn current_match(&self) -> Option<MdMatch> {
+        self.matches.get(self.current_match?).copied()
+    }
+
+    /// moves to next match and scrolls to it, wraps around after the last one
+    pub fn next_match(&mut self) -> Option<MdMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current_match = Some(match self.current_match {
+            Some(i) => (i + 1) % self.matches.len(),
+            None => 0,
+        });
+        let m = self.current_match()?;
+        self.scroll_into_view(m.line);
+        Some(m)
+    }
+
+    /// moves to previous match and scrolls to it, wraps around before the first one
+    pub fn prev_match(&mut self) -> Option<MdMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current_match = Some(match self.current_match {
+            Some(0) | None => self.matches.len() - 1,
+            Some(i) => i - 1,
+        });
+        let m = self.current_match()?;
+        self.scroll_into_view(m.line);
+        Some(m)
+    }
+
+    pub fn clear_matches(&mut self) {
+        self.matches.clear();
+        self.current_match = None;
+    }
+}
+
+/// widget rendering `MdDocument` line by line, without wrapping,
+/// so that links can be mapped to the screen
+#[derive(Debug, Clone)]
+pub struct MdView<'a> {
+    document: &'a MdDocument,
+    block: Option<Block<'a>>,
+    folding: bool,
+    focused_style: Style,
+    match_style: Style,
+    current_match_style: Style,
+}
+
+impl<'a> MdView<'a> {
+    pub fn new(document: &'a MdDocument) -> MdView<'a> {
+        MdView {
+            document,
+            block: None,
+            folding: false,
+            focused_style: document.style.focused_link,
+            match_style: document.style.search_match,
+            current_match_style: document.style.search_current,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> MdView<'a> {
+        self.block = Some(block);
+        self
+    }
+
+    /// draws fold indicators before headings, sections are folded with `MdViewState`
+    pub fn folding(mut self, folding: bool) -> MdView<'a> {
+        self.folding = folding;
+        self
+    }
+
+    pub fn focused_style(mut self, style: Style) -> MdView<'a> {
+        self.focused_style = style;
+        self
+    }
+
+    pub fn match_style(mut self, style: Style) -> MdView<'a> {
+        self.match_style = style;
+        self
+    }
+
+    pub fn current_match_style(mut self, style: Style) -> MdView<'a> {
+        self.current_match_style = style;
+        self
+    }
+}
+
+/// part of the `row` between columns `start` and `end` on the screen, if it's visible
+fn line_rect(area: Rect, scroll: u16, row: Option<usize>, start: usize, end: usize) -> Option<Rect> {
+    let row = row?.checked_sub(scroll as usize)?;
+    if row >= area.height as usize || start >= area.width as usize {
+        return None;
+    }
+    let end = end.min(area.width as usize);
+    Some(Rect {
+        x: area.x + start as u16,
+        y: area.y + row as u16,
+        width: (end - start) as u16,
+        height: 1,
+    })
+}
+
+impl<'a> StatefulWidget for MdView<'a> {
+    type State = MdViewState;
+
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let area = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        if state.links != self.document.links {
+            state.links = self.document.links.clone();
+            state.focused = state.focused.filter(|i| *i < state.links.len());
+        }
+        let line_count = self.document.text.lines.len();
+        if state.headings != self.document.headings || state.line_count != line_count {
+            state.headings = self.document.headings.clone();
+            let headings = &state.headings;
+            state.folded.retain(|l| headings.iter().any(|h| h.line == *l));
+            state.line_count = line_count;
+            state.layout();
+        }
+        state.area = area;
+        state.areas.clear();
+
+        let style = &self.document.style;
+        let symbols = &self.document.symbols;
+        let gutter: u16 = if self.folding { 2 } else { 0 };
+        let content = Rect {
+            x: area.x + gutter.min(area.width),
+            width: area.width.saturating_sub(gutter),
+            ..area
+        };
+
+        for (y, row) in state
+            .rows
+            .iter()
+            .skip(state.scroll as usize)
+            .take(area.height as usize)
+            .enumerate()
+        {
+            let y = area.y + y as u16;
+            match *row {
+                Row::Line(line) => {
+                    if self.folding && state.headings.iter().any(|h| h.line == line) {
+                        let symbol = if state.folded.contains(&line) { &symbols.folded } else { &symbols.unfolded };
+                        buf.set_stringn(area.x, y, symbol, area.width as usize, style.fold);
+                    }
+                    let text = &self.document.text.lines[line];
+                    buf.set_line(content.x, y, text, content.width);
+                }
+                Row::Summary(hidden) => {
+                    let unit = if hidden == 1 { "line" } else { "lines" };
+                    let summary = format!("{}{} {}", symbols.fold_summary, hidden, unit);
+                    buf.set_stringn(content.x, y, summary, content.width as usize, style.fold);
+                }
+            }
+        }
+
+        for (i, m) in state.matches.iter().enumerate() {
+            let row = state.row_of(m.line);
+            let Some(rect) = line_rect(content, state.scroll, row, m.start, m.end) else {
+                continue;
+            };
+            if state.current_match == Some(i) {
+                buf.set_style(rect, self.current_match_style);
+            } else {
+                buf.set_style(rect, self.match_style);
+            }
+        }
+
+        for (i, link) in state.links.iter().enumerate() {
+            let row = state.row_of(link.line);
+            let Some(rect) = line_rect(content, state.scroll, row, link.start, link.end) else {
+                continue;
+            };
+            if state.focused == Some(i) {
+                buf.set_style(rect, self.focused_style);
+            }
+            state.areas.push(LinkArea {
+                url: link.url.clone(),
+                target: link.target,
+                area: rect,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use ratatui::{
+        buffer::Buffer,
+        layout::Rect,
+        style::{Color, Modifier},
+        widgets::StatefulWidget,
+    };
+
+    use crate::{options::options::ParseOptions, style::symbols::MdSymbols, MarkdownParsable};
+
+    use super::{MdView, MdViewState};
+
+    #[test]
+    fn link_hit_testing() -> Result<()> {
+        let doc = "# title
+[one](a) text [two](b)
+
+[three](c)
+"
+        .parse_document(None)?;
+
+        let area = Rect::new(0, 0, 30, 2);
+        let mut buf = Buffer::empty(area);
+        let mut state = MdViewState::new();
+        MdView::new(&doc).render(area, &mut buf, &mut state);
+
+        assert_eq!(state.link_areas().len(), 2);
+        assert_eq!(state.link_at(0, 1), Some("a"));
+        assert_eq!(state.link_at(16, 1), Some("b"));
+        assert_eq!(state.link_at(9, 1), None);
+        assert_eq!(state.link_at(0, 0), None);
+
+        state.focus_prev();
+        assert_eq!(state.focused_link().map(|l| l.url.as_str()), Some("c"));
+        assert_eq!(state.scroll, 2);
+
+        state.focus_next();
+        assert_eq!(state.focused_link().map(|l| l.url.as_str()), Some("a"));
+        assert_eq!(state.scroll, 1);
+
+        MdView::new(&doc).render(area, &mut buf, &mut state);
+        assert!(buf.get(0, 0).modifier.contains(Modifier::REVERSED));
+        Ok(())
+    }
+
+    #[test]
+    fn anchor_links_scroll() -> Result<()> {
+        let doc = "[setup](#setup) [web](http)
+
+
+## Setup
+"
+        .parse_document(None)?;
+
+        let area = Rect::new(0, 0, 30, 2);
+        let mut buf = Buffer::empty(area);
+        let mut state = MdViewState::new();
+        MdView::new(&doc).render(area, &mut buf, &mut state);
+
+        assert_eq!(state.activate_at(17, 0), Some("http".into()));
+        assert_eq!(state.activate_at(1, 0), None);
+        assert_eq!(state.scroll, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn search_navigation() -> Result<()> {
+        let doc = "todo one
+two
+todo three
+todo four
+"
+        .parse_document(None)?;
+
+        let area = Rect::new(0, 0, 20, 2);
+        let mut buf = Buffer::empty(area);
+        let mut state = MdViewState::new();
+        MdView::new(&doc).render(area, &mut buf, &mut state);
+
+        state.set_matches(doc.search("todo"));
+        assert_eq!(state.current_match().map(|m| m.line), Some(0));
+        assert_eq!(state.next_match().map(|m| m.line), Some(2));
+        assert_eq!(state.scroll, 1);
+        assert_eq!(state.next_match().map(|m| m.line), Some(3));
+        assert_eq!(state.scroll, 2);
+        assert_eq!(state.prev_match().map(|m| m.line), Some(2));
+
+        MdView::new(&doc).render(area, &mut buf, &mut state);
+        assert_eq!(buf.get(0, 0).bg, doc.style.search_current.bg.unwrap());
+        assert_eq!(buf.get(0, 1).bg, doc.style.search_match.bg.unwrap());
+        assert_eq!(buf.get(4, 1).bg, Color::Reset);
+
+        state.clear_matches();
+        assert_eq!(state.next_match(), None);
+
+        // scroll counts rows, matches in the folded section above are skipped
+        let doc = "# A\ntodo\ntodo\n# B\ntodo\n".parse_document(None)?;
+        let mut state = MdViewState::new();
+        MdView::new(&doc).folding(true).render(area, &mut buf, &mut state);
+        state.fold(0);
+        state.scroll = 2;
+        state.set_matches(doc.search("todo"));
+        assert_eq!(state.current_match().map(|m| m.line), Some(4));
+        assert!(state.is_folded(0));
+        assert_eq!(state.scroll, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn folding_sections() -> Result<()> {
+        let doc = "# A
+a
+## B
+b
+[x](y)
+# C
+c
+"
+        .parse_document(None)?;
+
+        let area = Rect::new(0, 0, 14, 8);
+        let mut state = MdViewState::new();
+        let render = |state: &mut MdViewState| {
+            let mut buf = Buffer::empty(area);
+            MdView::new(&doc).folding(true).render(area, &mut buf, state);
+            buf
+        };
+        render(&mut state);
+
+        state.fold(2);
+        let mut buf = render(&mut state);
+        let expected = Buffer::with_lines(vec![
+            "- # A         ",
+            "  a           ",
+            "+ ## B        ",
+            "  ... 2 lines ",
+            "- # C         ",
+            "  c           ",
+            "              ",
+            "              ",
+        ]);
+        let symbols = |b: &Buffer| b.content().iter().map(|c| c.symbol.clone()).collect::<String>();
+        assert_eq!(symbols(&buf), symbols(&expected));
+        assert!(state.link_areas().is_empty());
+
+        state.fold_all();
+        render(&mut state);
+        state.toggle_fold_at(2);
+        buf = render(&mut state);
+        assert_eq!(buf.get(0, 2).symbol, "-");
+        assert_eq!(buf.get(2, 3).symbol, "c");
+
+        state.unfold_all();
+        state.fold(0);
+        render(&mut state);
+        state.focus_next();
+        assert!(!state.is_folded(0));
+        buf = render(&mut state);
+        assert_eq!(state.link_at(2, 4), Some("y"));
+        assert_eq!(buf.get(0, 0).symbol, "-");
+
+        // rows follow the folds without a render in between
+        state.fold_all();
+        state.unfold(0);
+        state.toggle_fold_at(4);
+        assert!(!state.is_folded(5) && state.is_folded(2));
+
+        let options = ParseOptions::default().symbols(MdSymbols::unicode());
+        let doc = "# A\na\n# B\n".parse_with_options(None, &options)?;
+        let area = Rect::new(0, 0, 14, 3);
+        let mut state = MdViewState::new();
+        let mut buf = Buffer::empty(area);
+        MdView::new(&doc).folding(true).render(area, &mut buf, &mut state);
+        state.fold(0);
+        MdView::new(&doc).folding(true).render(area, &mut buf, &mut state);
+        let expected = Buffer::with_lines(vec!["▸ ◉ A         ", "  ⋯ 1 line    ", "▾ ◉ B         "]);
+        assert_eq!(symbols(&buf), symbols(&expected));
+        Ok(())
+    }
+
+    #[test]
+    fn rows_past_u16() -> Result<()> {
+        let md = format!("{}# H\nh [l](u)\n", "x\n".repeat(65536));
+        let doc = md.as_str().parse_document(None)?;
+
+        let area = Rect::new(0, 0, 20, 2);
+        let mut buf = Buffer::empty(area);
+        let mut state = MdViewState::new();
+        MdView::new(&doc).folding(true).render(area, &mut buf, &mut state);
+
+        // rows past the u16 scroll are focused and folded without overflow
+        state.focus_next();
+        assert_eq!(state.focused_link().map(|l| l.line), Some(65537));
+        state.scroll = u16::MAX;
+        state.toggle_fold_at(1);
+        assert!(state.is_folded(65536));
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+//! Runs CommonMark spec examples from `tests/spec/commonmark.json` against the parser.
+//!
+//! Examples are compared on the plain text of every line, punctuation included:
+//! expected html is stripped of tags and entities, rendered `Text` is parsed with
+//! `ParseOptions::commonmark()`, lines are trimmed and blank lines are ignored on both sides.
+//! Markdown syntax the parser keeps on screen, like `#` of headings, fails the example.
+//!
+//! Examples listed in `tests/spec/known_failing.txt` may fail, any other failure is a
+//! regression. Examples from the list that pass fail the test too, so the list stays
+//! accurate. Rewrite the list with
+//! `SPEC_BLESS=1 cargo test --test commonmark`,
+//! pass rate of every section is printed with `-- --nocapture`.
+//! Examples that panic always fail the test, they can't be listed as known failures.
+use std::{collections::BTreeSet, env, fs, panic};
+
+use md_to_tui::{options::options::ParseOptions, MarkdownParsable};
+use regex::Regex;
+use serde::Deserialize;
+
+const SPEC: &str = "tests/spec/commonmark.json";
+const KNOWN_FAILING: &str = "tests/spec/known_failing.txt";
+
+#[derive(Debug, Deserialize)]
+struct Example {
+    markdown: String,
+    html: String,
+    example: usize,
+    section: String,
+}
+
+/// every non blank line, trimmed
+fn lines(text: &str) -> Vec<&str> {
+    text.lines().map(str::trim).filter(|l| !l.is_empty()).collect()
+}
+
+/// visible text of the html, block tags start new lines
+fn html_text(html: &str) -> String {
+    let block = Regex::new(r"(?i)</?(p|h[1-6]|li|ul|ol|pre|blockquote|hr|br|table|tr|th|td|div)\b[^>]*>").unwrap();
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+    let text = block.replace_all(html, "\n");
+    let text = tag.replace_all(&text, "");
+    decode_entities(&text)
+}
+
+fn decode_entities(text: &str) -> String {
+    let entity = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    entity
+        .replace_all(text, |c: &regex::Captures| {
+            let name = &c[1];
+            let code = if let Some(hex) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok()
+            } else if let Some(dec) = name.strip_prefix('#') {
+                dec.parse().ok()
+            } else {
+                None
+            };
+            match (code.and_then(char::from_u32), name) {
+                (Some(c), _) => c.to_string(),
+                (None, "amp") => "&".into(),
+                (None, "lt") => "<".into(),
+                (None, "gt") => ">".into(),
+                (None, "quot") => "\"".into(),
+                (None, "nbsp") => " ".into(),
+                _ => c[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail,
+    Panic,
+}
+
+/// rendered text of the markdown, `Err` with the panic message if parsing panicked
+fn render(markdown: &str) -> Result<Option<String>, String> {
+    let options = ParseOptions::commonmark();
+    let markdown = markdown.to_string();
+    let doc = panic::catch_unwind(|| markdown.parse_with_options(None, &options)).map_err(|e| {
+        e.downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_default()
+    })?;
+    let Ok(doc) = doc else {
+        return Ok(None);
+    };
+    let lines = doc
+        .text
+        .lines
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    Ok(Some(lines.join("\n")))
+}
+
+fn outcome(example: &Example) -> Outcome {
+    match render(&example.markdown) {
+        Err(message) => {
+            eprintln!("example {} panicked: {}", example.example, message);
+            Outcome::Panic
+        }
+        Ok(Some(text)) if lines(&text) == lines(&html_text(&example.html)) => Outcome::Pass,
+        Ok(_) => Outcome::Fail,
+    }
+}
+
+fn known_failing() -> BTreeSet<usize> {
+    fs::read_to_string(KNOWN_FAILING)
+        .unwrap_or_default()
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or_default().trim())
+        .filter_map(|l| l.parse().ok())
+        .collect()
+}
+
+/// pass rate of every section in spec order
+fn report(examples: &[Example], failing: &BTreeSet<usize>) -> String {
+    let mut sections: Vec<(&str, usize, usize)> = Vec::new();
+    for e in examples {
+        if sections.last().map(|s| s.0) != Some(e.section.as_str()) {
+            sections.push((&e.section, 0, 0));
+        }
+        let section = sections.last_mut().unwrap();
+        section.2 += 1;
+        if !failing.contains(&e.example) {
+            section.1 += 1;
+        }
+    }
+
+    let mut out = String::new();
+    for (name, passed, total) in &sections {
+        out.push_str(&format!(
+            "{:<45} {:>4}/{:<4} {:>5.1}%\n",
+            name,
+            passed,
+            total,
+            100.0 * *passed as f64 / *total as f64
+        ));
+    }
+    let passed = examples.len() - failing.len();
+    out.push_str(&format!(
+        "{:<45} {:>4}/{:<4} {:>5.1}%\n",
+        "total",
+        passed,
+        examples.len(),
+        100.0 * passed as f64 / examples.len() as f64
+    ));
+    out
+}
+
+#[test]
+fn commonmark_spec() -> anyhow::Result<()> {
+    let examples: Vec<Example> = serde_json::from_str(&fs::read_to_string(SPEC)?)?;
+
+    let outcomes = examples.iter().map(|e| (e.example, outcome(e))).collect::<Vec<_>>();
+    let failing = outcomes
+        .iter()
+        .filter(|(_, o)| *o != Outcome::Pass)
+        .map(|(e, _)| *e)
+        .collect::<BTreeSet<_>>();
+    let panicked = outcomes
+        .iter()
+        .filter(|(_, o)| *o == Outcome::Panic)
+        .map(|(e, _)| *e)
+        .collect::<Vec<_>>();
+
+    println!("{}", report(&examples, &failing));
+    assert!(panicked.is_empty(), "examples panicked: {:?}", panicked);
+
+    if env::var("SPEC_BLESS").is_ok_and(|v| v == "1") {
+        let mut list = String::from("# CommonMark spec examples the parser does not pass yet,\n");
+        list.push_str("# rewrite with `SPEC_BLESS=1 cargo test --test commonmark`\n");
+        let mut section = "";
+        for e in examples.iter().filter(|e| failing.contains(&e.example)) {
+            if e.section != section {
+                section = &e.section;
+                list.push_str(&format!("\n# {}\n", section));
+            }
+            list.push_str(&format!("{}\n", e.example));
+        }
+        fs::write(KNOWN_FAILING, list)?;
+        return Ok(());
+    }
+
+    let known = known_failing();
+    let regressed = failing.difference(&known).collect::<Vec<_>>();
+    let fixed = known.difference(&failing).collect::<Vec<_>>();
+    assert!(regressed.is_empty(), "examples regressed: {:?}", regressed);
+    assert!(
+        fixed.is_empty(),
+        "examples pass now, remove them from {} or run with SPEC_BLESS=1: {:?}",
+        KNOWN_FAILING,
+        fixed
+    );
+    Ok(())
+}
+
+#[test]
+fn html_to_lines() {
+    let html = "<h1>Title &amp; more</h1>\n<p>a <em>b</em>\nc&#33;</p>\n<hr />\n";
+    assert!(html_text(html).starts_with("\nTitle & more\n"));
+    assert_eq!(lines(&html_text(html)), vec!["Title & more", "a b", "c!"]);
+    assert_eq!(lines("  # Title \n\n  x"), vec!["# Title", "x"]);
+}